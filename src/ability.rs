@@ -0,0 +1,379 @@
+use crate::{
+    atoms::{Atom, ForcedFission},
+    bullet::spawner::{Lifespan, Uptime},
+    mouse::MousePosition,
+    player::Player,
+    pool::Pooled,
+    shaders::{materials::HeptaMaterial, Crimson, SpaceHaze},
+    should_run_game, Health,
+};
+use fxhash::FxHashSet;
+use std::f32::consts::{PI, TAU};
+use winny::{
+    asset::server::AssetServer,
+    ecs::sets::IntoSystemStorage,
+    gfx::{
+        cgmath::{Quaternion, Rad, Rotation3},
+        mesh2d::{Mesh2d, Points},
+    },
+    math::vector::{Vec2f, Vec3f, Vec4f},
+    prelude::*,
+};
+
+#[derive(Debug)]
+pub struct AbilityPlugin;
+
+impl Plugin for AbilityPlugin {
+    fn build(&mut self, app: &mut App) {
+        app.insert_resource(Abilities::default())
+            .add_systems(
+                AppSchedule::PostStartUp,
+                |mut abilities: ResMut<Abilities>, mut assets: ResMut<Assets<Mesh2d>>| {
+                    abilities.build_meshes(&mut assets);
+                },
+            )
+            .add_systems(
+                Schedule::Update,
+                (cast_abilities, apply_areas).run_if(should_run_game),
+            );
+    }
+}
+
+/// Opacity of an area when it is cast, it fades out over its duration.
+const AREA_ALPHA: f32 = 0.35;
+
+/// The region an ability affects, relative to where it is cast.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AoeShape {
+    Circle {
+        radius: f32,
+    },
+    Ring {
+        inner: f32,
+        outer: f32,
+    },
+    /// A slice of a circle centered on the direction the ability is cast in.
+    Cone {
+        radius: f32,
+        /// Radians.
+        angle: f32,
+    },
+}
+
+impl AoeShape {
+    /// Whether `point` is inside the shape cast at `center` facing `facing` radians.
+    pub fn contains(&self, center: Vec3f, facing: f32, point: Vec3f) -> bool {
+        let distance = point.dist2(&center);
+        match *self {
+            Self::Circle { radius } => distance <= radius * radius,
+            Self::Ring { inner, outer } => distance >= inner * inner && distance <= outer * outer,
+            Self::Cone { radius, angle } => {
+                if distance > radius * radius {
+                    return false;
+                }
+
+                let offset = point - center;
+                let difference = (offset.y.atan2(offset.x) - facing + PI).rem_euclid(TAU) - PI;
+                difference.abs() <= angle / 2.
+            }
+        }
+    }
+
+    /// An outline of the shape facing along the x axis.
+    fn mesh(&self) -> Mesh2d {
+        const SEGMENTS: usize = 32;
+        let at = |r: f32, i: usize| {
+            let theta = i as f32 / SEGMENTS as f32 * TAU;
+            Vec2f::new(r * theta.cos(), r * theta.sin())
+        };
+
+        let mut points = Points::default();
+        match *self {
+            Self::Circle { radius } => {
+                for i in 0..SEGMENTS {
+                    points.add(at(radius, i));
+                }
+            }
+            // around the outer circle and back around the inner one, the seam at angle 0 joins
+            // them into a single outline with a hole
+            Self::Ring { inner, outer } => {
+                for i in 0..=SEGMENTS {
+                    points.add(at(outer, i));
+                }
+                for i in (0..=SEGMENTS).rev() {
+                    points.add(at(inner, i));
+                }
+            }
+            Self::Cone { radius, angle } => {
+                points.add(Vec2f::new(0., 0.));
+                for i in 0..=SEGMENTS / 2 {
+                    let theta = -angle / 2. + angle * i as f32 / (SEGMENTS / 2) as f32;
+                    points.add(Vec2f::new(radius * theta.cos(), radius * theta.sin()));
+                }
+            }
+        }
+
+        Mesh2d::from_points(points).unwrap()
+    }
+}
+
+/// Where an ability is cast.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AoeAnchor {
+    /// Around the player, following them while the area lasts.
+    Player,
+    /// At the crosshair.
+    Cursor,
+}
+
+/// An area of effect the player can cast.
+#[derive(Debug, Clone)]
+pub struct Ability {
+    pub name: &'static str,
+    pub key: KeyCode,
+    pub shape: AoeShape,
+    pub anchor: AoeAnchor,
+    /// Seconds the area lasts, anything entering it is hit.
+    pub duration: f32,
+    /// Seconds before the ability can be cast again.
+    pub cooldown: f32,
+    /// Damage dealt once to everything with [Health] inside.
+    pub damage: f32,
+    /// Whether atoms inside are split.
+    pub fission: bool,
+    pub color: Vec4f,
+    pub sound: &'static str,
+}
+
+impl Ability {
+    pub fn nova() -> Self {
+        Self {
+            name: "nova",
+            key: KeyCode::J,
+            shape: AoeShape::Ring {
+                inner: 60.,
+                outer: 180.,
+            },
+            anchor: AoeAnchor::Player,
+            duration: 1.,
+            cooldown: 6.,
+            damage: 2.,
+            fission: true,
+            color: SpaceHaze::white(),
+            sound: "res/RPG_Essentials_Free/10_Battle_SFX/55_Encounter_02.wav",
+        }
+    }
+
+    pub fn sweep() -> Self {
+        Self {
+            name: "sweep",
+            key: KeyCode::K,
+            shape: AoeShape::Cone {
+                radius: 320.,
+                angle: PI / 4.,
+            },
+            anchor: AoeAnchor::Player,
+            duration: 0.25,
+            cooldown: 3.,
+            damage: 3.,
+            fission: false,
+            color: SpaceHaze::pink(),
+            sound: "res/RPG_Essentials_Free/10_Battle_SFX/22_Slash_04.wav",
+        }
+    }
+
+    pub fn burst() -> Self {
+        Self {
+            name: "burst",
+            key: KeyCode::L,
+            shape: AoeShape::Circle { radius: 120. },
+            anchor: AoeAnchor::Cursor,
+            duration: 0.5,
+            cooldown: 10.,
+            damage: 4.,
+            fission: true,
+            color: Crimson::color(0),
+            sound: "res/RPG_Essentials_Free/10_Battle_SFX/15_Impact_flesh_02.wav",
+        }
+    }
+
+    fn audio(&self, server: &AssetServer) -> AudioBundle {
+        AudioBundle {
+            handle: server.load(self.sound),
+            playback_settings: PlaybackSettings::default().with_volume(10.),
+        }
+    }
+}
+
+/// The player's abilities and how long until each can be cast again.
+#[derive(Debug, Resource)]
+pub struct Abilities {
+    abilities: Vec<Ability>,
+    cooldowns: Vec<f32>,
+    meshes: Vec<Option<Handle<Mesh2d>>>,
+}
+
+impl Default for Abilities {
+    fn default() -> Self {
+        Self::new(vec![Ability::nova(), Ability::sweep(), Ability::burst()])
+    }
+}
+
+impl Abilities {
+    pub fn new(abilities: Vec<Ability>) -> Self {
+        Self {
+            cooldowns: vec![0.; abilities.len()],
+            meshes: vec![None; abilities.len()],
+            abilities,
+        }
+    }
+
+    /// Every ability with the seconds left on its cooldown.
+    pub fn iter(&self) -> impl Iterator<Item = (&Ability, f32)> {
+        self.abilities.iter().zip(self.cooldowns.iter().copied())
+    }
+
+    fn build_meshes(&mut self, assets: &mut Assets<Mesh2d>) {
+        for (ability, mesh) in self.abilities.iter().zip(self.meshes.iter_mut()) {
+            *mesh = Some(assets.add(ability.shape.mesh()));
+        }
+    }
+}
+
+/// A timed area left by an [Ability], timed with [Lifespan] and [Uptime].
+///
+/// Everything inside is hit once, no matter how long it stays.
+#[derive(Debug, Component)]
+pub struct AoeArea {
+    shape: AoeShape,
+    /// Radians.
+    facing: f32,
+    damage: f32,
+    fission: bool,
+    /// The entity the area moves with.
+    follow: Option<Entity>,
+    hit: FxHashSet<Entity>,
+}
+
+fn cast_abilities(
+    player: Query<(Entity, Transform), With<Player>>,
+    keys: EventReader<KeyInput>,
+    mouse: Res<MousePosition>,
+    mut abilities: ResMut<Abilities>,
+    mut commands: Commands,
+    server: Res<AssetServer>,
+    delta: Res<DeltaTime>,
+) {
+    for cooldown in abilities.cooldowns.iter_mut() {
+        *cooldown = (*cooldown - delta.delta).max(0.);
+    }
+
+    let Ok((player, transform)) = player.get_single() else {
+        return;
+    };
+
+    for event in keys.peak_read().filter(|k| k.state == KeyState::Pressed) {
+        let Some(index) = abilities.abilities.iter().position(|a| a.key == event.code) else {
+            continue;
+        };
+        if abilities.cooldowns[index] > 0. {
+            continue;
+        }
+        let Some(mesh) = abilities.meshes[index].clone() else {
+            continue;
+        };
+
+        let ability = &abilities.abilities[index];
+        let cursor: Vec3f = mouse.0.into();
+        let aim = cursor - transform.translation;
+        let facing = aim.y.atan2(aim.x);
+        let (center, follow) = match ability.anchor {
+            AoeAnchor::Player => (transform.translation, Some(player)),
+            AoeAnchor::Cursor => (cursor, None),
+        };
+
+        commands.spawn((
+            AoeArea {
+                shape: ability.shape,
+                facing,
+                damage: ability.damage,
+                fission: ability.fission,
+                follow,
+                hit: FxHashSet::default(),
+            },
+            Transform {
+                translation: center,
+                rotation: Quaternion::from_angle_z(Rad(facing)),
+                ..Default::default()
+            },
+            Lifespan(ability.duration),
+            Uptime(0.),
+            mesh,
+            HeptaMaterial {
+                modulation: Modulation(Vec4f::new(
+                    ability.color.x,
+                    ability.color.y,
+                    ability.color.z,
+                    AREA_ALPHA,
+                )),
+            },
+        ));
+        commands.spawn(ability.audio(&server));
+
+        abilities.cooldowns[index] = ability.cooldown;
+    }
+}
+
+fn apply_areas(
+    mut areas: Query<(
+        Mut<AoeArea>,
+        Mut<Transform>,
+        Mut<HeptaMaterial>,
+        Uptime,
+        Lifespan,
+    )>,
+    followed: Query<Transform, Without<AoeArea>>,
+    mut damaged: Query<(Entity, Transform, Mut<Health>), (Without<Player>, Without<AoeArea>)>,
+    atoms: Query<(Entity, Transform), (With<Atom>, Without<Pooled>, Without<AoeArea>)>,
+    mut forced: EventWriter<ForcedFission>,
+) {
+    for (area, transform, material, uptime, lifespan) in areas.iter_mut() {
+        if let Some(target) = area.follow.and_then(|e| followed.get(e)) {
+            transform.translation = target.translation;
+        }
+        material.modulation.0.w = AREA_ALPHA * (1. - uptime.0 / lifespan.0.max(f32::EPSILON));
+
+        let center = transform.translation;
+        let (shape, facing) = (area.shape, area.facing);
+
+        for (target, target_transform, health) in damaged.iter_mut() {
+            if !shape.contains(center, facing, target_transform.translation)
+                || !area.hit.insert(target)
+            {
+                continue;
+            }
+
+            health.set_current(health.current() - area.damage);
+        }
+
+        if !area.fission {
+            continue;
+        }
+
+        for (atom, atom_transform) in atoms.iter() {
+            let position = atom_transform.translation;
+            if !shape.contains(center, facing, position) || !area.hit.insert(atom) {
+                continue;
+            }
+
+            // fission products are thrown away from the center
+            let outward = position - center;
+            let direction = if outward.is_zero() {
+                Vec3f::new(1., 0., 0.)
+            } else {
+                outward.normalize()
+            };
+            forced.send(ForcedFission { atom, direction });
+        }
+    }
+}
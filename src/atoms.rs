@@ -1,13 +1,26 @@
 use crate::{
     audio::AudioMaster,
-    bullet::{NeutronBundle, Progenitor, RadialVelocity},
-    camera::{PlayerCamera, ScreenShake},
+    budget::EntityBudget,
+    bullet::{pierce::HitBudget, ExtraYield, NeutronBundle, Progenitor, RadialVelocity},
+    camera::shake_on_fission,
+    capture::Isotope,
     collision::{CircleCollider, CollideWithPlayer, Collider, EnemyCollideEvent},
+    debris::scatter_debris,
+    delayed::DelayedEmitter,
+    lineage::{Lineage, LineageConfig},
+    pool::{EntityPool, Pooled},
+    radiation::emit_radiation,
+    reactor::{Criticality, SourceNeutron},
+    recording::record_fissions,
     regular::{PolygonMaterials, RegularPolygons},
-    should_run_game, CollisionDamage, Enemy, GetOrLog, RandomDirectionIterator, Velocity,
+    rods::ControlRod,
+    shockwave::spawn_shockwaves,
+    should_run_game,
+    upgrade::gain_exp,
+    CollisionDamage, Enemy, GetOrLog, RandomDirectionIterator, Velocity,
 };
 use angle::Radf;
-use fxhash::FxHashSet;
+use fxhash::{FxHashMap, FxHashSet};
 use mesh2d::Mesh2d;
 use rand::{Rng, SeedableRng};
 use server::AssetServer;
@@ -21,7 +34,23 @@ pub struct AtomPlugin;
 impl Plugin for AtomPlugin {
     fn build(&mut self, app: &mut App) {
         app.insert_resource(TotalEvents::default())
-            .add_systems(Schedule::PostUpdate, handle_neutron.run_if(should_run_game));
+            .register_event::<FissionEvent>()
+            .register_event::<ForcedFission>()
+            .add_systems(
+                Schedule::PostUpdate,
+                // everything that reacts to fissions is registered after them, so it sees the
+                // fissions of the frame they happen in
+                (
+                    handle_neutron,
+                    shake_on_fission,
+                    emit_radiation,
+                    spawn_shockwaves,
+                    record_fissions,
+                    scatter_debris,
+                    gain_exp,
+                )
+                    .run_if(should_run_game),
+            );
     }
 }
 
@@ -43,6 +72,9 @@ pub struct AtomBundle {
     mesh: Handle<Mesh2d>,
     radial: RadialVelocity,
     with_player: CollideWithPlayer,
+    delayed: DelayedEmitter,
+    isotope: Isotope,
+    lineage: Lineage,
 }
 
 impl AtomBundle {
@@ -55,7 +87,21 @@ impl AtomBundle {
         events: u32,
         server: &AssetServer,
         _audio: &mut AudioMaster,
+        pool: &mut EntityPool,
     ) -> Entity {
+        if let Some(entity) = pool.take_atom(events) {
+            commands
+                .get_entity(entity)
+                .remove::<Pooled>()
+                .insert(Self::transform(position))
+                .insert(Progenitor(progenitor))
+                .insert(Velocity(velocity.unwrap_or_default()))
+                .insert(DelayedEmitter::default())
+                .insert(Isotope::default())
+                .insert(Lineage::default());
+            return entity;
+        }
+
         // audio.queue_bundle(AudioBundle {
         //     handle: server.load("res/RPG_Essentials_Free/10_Battle_SFX/77_flesh_02.wav"),
         //     playback_settings: PlaybackSettings::default().with_volume(10.0),
@@ -88,11 +134,7 @@ impl AtomBundle {
         AtomBundle {
             atom: Atom,
             enemy: Enemy,
-            transform: Transform {
-                translation: position,
-                scale: Vec2f::new(0.5, 0.5),
-                ..Default::default()
-            },
+            transform: Self::transform(position),
             // velocity: Velocity(velocity),
             collider: Collider::Circle(CircleCollider {
                 radius: 40.,
@@ -106,6 +148,17 @@ impl AtomBundle {
                 PI + rand::rngs::SmallRng::from_entropy().gen_range(-1f32..1f32),
             )),
             with_player: CollideWithPlayer,
+            delayed: DelayedEmitter::default(),
+            isotope: Isotope::default(),
+            lineage: Lineage::default(),
+        }
+    }
+
+    fn transform(position: Vec3f) -> Transform {
+        Transform {
+            translation: position,
+            scale: Vec2f::new(0.5, 0.5),
+            ..Default::default()
         }
     }
 }
@@ -113,53 +166,206 @@ impl AtomBundle {
 #[derive(Debug, Resource, Default)]
 pub struct TotalEvents(pub usize);
 
+/// Sent by [handle_neutron] for every atom that splits.
+#[derive(Debug, Clone, Copy, Event)]
+pub struct FissionEvent {
+    pub position: Vec3f,
+    /// The generation of the atom that split.
+    pub generation: u32,
+    /// The atom that split, it has already been despawned or pooled.
+    pub parent: Entity,
+    /// The first fission of the chain reaction this one belongs to.
+    pub chain_id: u32,
+    pub neutrons_spawned: u32,
+}
+
+/// Splits an atom without a neutron, it can't be captured and isn't part of a chain reaction.
+///
+/// Handled by [handle_neutron] along with the neutrons that hit atoms.
+#[derive(Debug, Clone, Copy, Event)]
+pub struct ForcedFission {
+    pub atom: Entity,
+    /// The direction the fission products are thrown in.
+    pub direction: Vec3f,
+}
+
 fn handle_neutron(
-    q: Query<(Entity, Transform, Option<Velocity>, Progenitor, Events), With<Atom>>,
-    bullets: Query<(Entity, Transform, Velocity, Progenitor)>,
+    q: Query<
+        (
+            Entity,
+            Transform,
+            Option<Velocity>,
+            Progenitor,
+            Events,
+            Isotope,
+            Lineage,
+        ),
+        (With<Atom>, Without<Pooled>),
+    >,
+    bullets: Query<
+        (
+            Entity,
+            Transform,
+            Velocity,
+            Progenitor,
+            Option<CollideWithPlayer>,
+            Lineage,
+            HitBudget,
+            CollisionDamage,
+            ExtraYield,
+        ),
+        Without<Pooled>,
+    >,
+    rods: Query<ControlRod>,
+    sources: Query<SourceNeutron>,
     reader: EventReader<EnemyCollideEvent>,
+    forced: EventReader<ForcedFission>,
     mut commands: Commands,
     server: Res<AssetServer>,
     mut total_events: ResMut<TotalEvents>,
     polygons: Res<RegularPolygons>,
-    mut camera: ResMut<PlayerCamera>,
-    delta: Res<DeltaTime>,
     mut audio: ResMut<AudioMaster>,
+    mut criticality: ResMut<Criticality>,
+    budget: Res<EntityBudget>,
+    mut pool: ResMut<EntityPool>,
+    lineage: Res<LineageConfig>,
+    mut writer: EventWriter<FissionEvent>,
 ) {
     let mut already_handled = FxHashSet::default();
+    // neutrons released this frame, their later collisions are stale
+    let mut spent = FxHashSet::default();
+    // neutrons that pierce can hit several atoms in a frame
+    let mut piercing = FxHashMap::default();
+    let mut rng = rand::rngs::SmallRng::from_entropy();
+
+    // Neutrons that touched a deployed control rod this frame are absorbed and can't cause fission,
+    // unless they bounce off it.
+    let absorbed = reader
+        .peak_read()
+        .filter(|e| rods.get(e.enemy).is_some_and(|r| r.is_deployed()))
+        .filter(|e| !bullets.get(e.with).is_some_and(|b| b.6.bounces()))
+        .map(|e| e.with)
+        .collect::<FxHashSet<_>>();
+
+    let neutron_hits = reader
+        .peak_read()
+        .filter(|e| !absorbed.contains(&e.with))
+        .filter_map(|e| {
+            bullets
+                .get_or_log(e.with)
+                .and_then(|b| Some((q.get_or_log(e.enemy)?, Some(b), b.2 .0)))
+        });
+    let forced_hits = forced
+        .peak_read()
+        .filter_map(|f| Some((q.get(f.atom)?, None, f.direction)));
 
     for (
-        (atom, atom_position, atom_velocity, atom_progenitor, events),
-        (bullet, _bullet_transform, bullet_velocity, progenitor),
-    ) in reader.peak_read().filter_map(|e| {
-        bullets
-            .get_or_log(e.with)
-            .and_then(|b| Some((q.get_or_log(e.enemy)?, b)))
-    }) {
-        if !already_handled.insert(atom) {
-            continue;
-        }
-        match (atom_progenitor.0, progenitor.0) {
-            (Some(atom_progenitor), Some(progenitor)) if atom_progenitor == progenitor => continue,
-            _ => {}
-        }
+        (atom, atom_position, atom_velocity, atom_progenitor, events, isotope, atom_lineage),
+        bullet,
+        push,
+    ) in neutron_hits.chain(forced_hits)
+    {
+        // the lineage of whatever split the atom, whether it was part of a chain reaction
+        // and the extra neutrons it carries
+        let (cause, chained, extra_yield) = match bullet {
+            Some((
+                bullet,
+                bullet_transform,
+                bullet_velocity,
+                progenitor,
+                hits_player,
+                bullet_lineage,
+                hit_budget,
+                damage,
+                extra_yield,
+            )) => {
+                if lineage.is_immune(atom_lineage, bullet_lineage) {
+                    continue;
+                }
+                if spent.contains(&bullet) || !already_handled.insert(atom) {
+                    continue;
+                }
+                match (atom_progenitor.0, progenitor.0) {
+                    (Some(atom_progenitor), Some(progenitor)) if atom_progenitor == progenitor => {
+                        continue
+                    }
+                    _ => {}
+                }
+
+                // captured neutrons are absorbed without splitting the atom
+                if isotope.is_inert() || isotope.captures(&mut rng) {
+                    pool.release_neutron(bullet, hits_player.is_some(), &mut commands);
+                    spent.insert(bullet);
+                    if progenitor.0.is_some() && sources.get(bullet).is_none() {
+                        criticality.record_losses(1);
+                    }
+                    if !isotope.is_inert() {
+                        commands.get_entity(atom).insert(isotope.captured());
+                    }
+                    continue;
+                }
 
-        commands.get_entity(atom).despawn();
-        commands.get_entity(bullet).despawn();
+                let (hits, velocity, damage) =
+                    piercing
+                        .entry(bullet)
+                        .or_insert((*hit_budget, bullet_velocity.0, damage.0));
+                let pierced = hits.spend(
+                    bullet,
+                    bullet_transform.translation,
+                    velocity,
+                    damage,
+                    atom_position.translation,
+                    &mut commands,
+                );
+                if !pierced {
+                    pool.release_neutron(bullet, hits_player.is_some(), &mut commands);
+                    spent.insert(bullet);
+                    if progenitor.0.is_some() && sources.get(bullet).is_none() {
+                        criticality.record_losses(1);
+                    }
+                }
+
+                (*bullet_lineage, progenitor.0.is_some(), extra_yield.0)
+            }
+            // forced fissions split the atom as if hit by a neutron that can't be captured
+            None => {
+                if isotope.is_inert() || !already_handled.insert(atom) {
+                    continue;
+                }
+                (Lineage::default(), false, 0)
+            }
+        };
+
+        pool.release_atom(atom, events.0, &mut commands);
         total_events.0 += 1;
+        let fission = total_events.0 as u32;
+        let child = atom_lineage.child_by(&cause, fission);
+
+        let mut fission_event = FissionEvent {
+            position: atom_position.translation,
+            generation: events.0,
+            parent: atom,
+            chain_id: child.chain().unwrap_or(fission),
+            neutrons_spawned: 0,
+        };
 
         if events.0 >= 6 {
+            writer.send(fission_event);
             continue;
         }
 
-        let direction = bullet_velocity.0 + atom_velocity.map_or(Default::default(), |v| v.0);
+        let direction = push + atom_velocity.map_or(Default::default(), |v| v.0);
         // let direction =
         //     bullet_velocity.0 + (atom_position.translation - bullet_transform.translation) * 0.25;
         let direction = direction.normalize();
 
         let directions = RandomDirectionIterator::new(direction, Radf(FRAC_PI_2));
 
-        for direction in directions.clone().take(2) {
-            AtomBundle::spawn(
+        let fragments = budget.scale_yield(2);
+        let neutrons = budget.scale_yield(3 + isotope.bonus_neutrons() + extra_yield);
+
+        for direction in directions.clone().take(fragments) {
+            let fragment = AtomBundle::spawn(
                 &mut commands,
                 atom_position.translation,
                 Some(direction),
@@ -168,11 +374,17 @@ fn handle_neutron(
                 events.0 + 1,
                 &server,
                 &mut audio,
+                &mut pool,
             );
+            commands.get_entity(fragment).insert(child);
         }
 
-        for direction in directions.take(3) {
-            NeutronBundle::spawn(
+        // fissions started from outside the reactor are a source, not part of the chain
+        if chained {
+            criticality.record_births(neutrons as u32);
+        }
+        for direction in directions.take(neutrons) {
+            let neutron = NeutronBundle::spawn(
                 &server,
                 Transform {
                     translation: atom_position.translation,
@@ -183,15 +395,15 @@ fn handle_neutron(
                 Some(atom),
                 true,
                 &mut commands,
+                &mut pool,
             );
+            commands.get_entity(neutron).insert(child);
+            if !chained {
+                commands.get_entity(neutron).insert(SourceNeutron);
+            }
         }
-    }
 
-    if !already_handled.is_empty() {
-        camera.push_screen_shake(ScreenShake::new(
-            10.,
-            0.15,
-            delta.wrapping_elapsed_as_seconds(),
-        ));
+        fission_event.neutrons_spawned = neutrons as u32;
+        writer.send(fission_event);
     }
 }
@@ -0,0 +1,291 @@
+use crate::{
+    atoms::{Atom, Events},
+    bullet::{homing::Homing, pierce::HitBudget, spawner::Uptime, Neutron, Progenitor},
+    collision::CollideWithPlayer,
+    lineage::{Lineage, LineageConfig},
+    pool::{EntityPool, Pooled},
+    reactor::{Criticality, SourceNeutron},
+    should_run_game, Velocity,
+};
+use fxhash::{FxHashMap, FxHashSet};
+use winny::{ecs::sets::IntoSystemStorage, prelude::*};
+
+#[derive(Debug)]
+pub struct BudgetPlugin;
+
+impl Plugin for BudgetPlugin {
+    fn build(&mut self, app: &mut App) {
+        app.insert_resource(EntityBudget::default())
+            .egui_resource::<EntityBudget>()
+            .register_event::<BudgetExceeded>()
+            .add_systems(Schedule::Update, toggle_readout)
+            .add_systems(
+                Schedule::PostUpdate,
+                (count_entities, merge_neutrons, enforce_hard_limit).run_if(should_run_game),
+            );
+    }
+}
+
+/// Sent when the number of atoms and neutrons crosses the hard limit.
+#[derive(Debug, Clone, Copy, Event)]
+pub struct BudgetExceeded {
+    pub atoms: usize,
+    pub neutrons: usize,
+}
+
+/// A global budget for atoms and neutrons.
+///
+/// Every fission spawns five entities, so a dense field grows exponentially. Above the soft
+/// limit fission yields are lowered and nearby neutrons are merged, above the hard limit the
+/// oldest neutrons and the latest fragments are despawned.
+#[derive(Debug, Resource, AsEgui)]
+pub struct EntityBudget {
+    soft_limit: usize,
+    hard_limit: usize,
+    /// Neutrons closer than this are merged when over the soft limit.
+    merge_radius: f32,
+    show_readout: bool,
+    #[skip]
+    atoms: usize,
+    #[skip]
+    neutrons: usize,
+    #[skip]
+    exceeded: bool,
+    /// Neutrons merged away this frame, they are still around until the commands apply.
+    #[skip]
+    merged: FxHashSet<Entity>,
+}
+
+impl Default for EntityBudget {
+    fn default() -> Self {
+        Self {
+            soft_limit: 400,
+            hard_limit: 800,
+            merge_radius: 20.0,
+            show_readout: false,
+            atoms: 0,
+            neutrons: 0,
+            exceeded: false,
+            merged: FxHashSet::default(),
+        }
+    }
+}
+
+impl EntityBudget {
+    pub fn atoms(&self) -> usize {
+        self.atoms
+    }
+
+    pub fn neutrons(&self) -> usize {
+        self.neutrons
+    }
+
+    pub fn total(&self) -> usize {
+        self.atoms + self.neutrons
+    }
+
+    pub fn soft_limit(&self) -> usize {
+        self.soft_limit
+    }
+
+    pub fn hard_limit(&self) -> usize {
+        self.hard_limit
+    }
+
+    pub fn show_readout(&self) -> bool {
+        self.show_readout
+    }
+
+    /// How far between the soft and hard limit the budget is, in the range [0, 1].
+    pub fn pressure(&self) -> f32 {
+        if self.total() <= self.soft_limit {
+            return 0.0;
+        }
+
+        let range = self.hard_limit.saturating_sub(self.soft_limit).max(1);
+        ((self.total() - self.soft_limit) as f32 / range as f32).min(1.0)
+    }
+
+    pub fn over_soft_limit(&self) -> bool {
+        self.total() > self.soft_limit
+    }
+
+    /// Scales a fission yield down as the budget fills up, never below one.
+    pub fn scale_yield(&self, base: usize) -> usize {
+        let scaled = (base as f32 * (1.0 - self.pressure())).round() as usize;
+        scaled.clamp(1, base.max(1))
+    }
+}
+
+fn toggle_readout(input: EventReader<KeyInput>, mut budget: ResMut<EntityBudget>) {
+    if input.peak_read().any(|k| {
+        matches!(
+            k,
+            KeyInput {
+                code: KeyCode::B,
+                state: KeyState::Pressed,
+                ..
+            }
+        )
+    }) {
+        budget.show_readout = !budget.show_readout;
+    }
+}
+
+fn count_entities(
+    atoms: Query<Entity, (With<Atom>, Without<Pooled>)>,
+    neutrons: Query<Entity, (With<Neutron>, Without<Pooled>)>,
+    mut budget: ResMut<EntityBudget>,
+    mut writer: EventWriter<BudgetExceeded>,
+) {
+    budget.atoms = atoms.iter().count();
+    budget.neutrons = neutrons.iter().count();
+    budget.merged.clear();
+
+    let exceeded = budget.total() > budget.hard_limit;
+    if exceeded && !budget.exceeded {
+        warn!(
+            "entity budget exceeded: {} atoms, {} neutrons",
+            budget.atoms, budget.neutrons
+        );
+        writer.send(BudgetExceeded {
+            atoms: budget.atoms,
+            neutrons: budget.neutrons,
+        });
+    }
+    budget.exceeded = exceeded;
+}
+
+/// Merges fission neutrons that share a cell into one, averaging their velocity.
+///
+/// Only neutrons that behave the same are merged, those that hit the player, are sources or
+/// belong to different chains stay apart, and neutrons that pierce, home or are still immune
+/// are never merged. The merged neutrons live on in the survivor, so they are not losses for
+/// the [Criticality].
+fn merge_neutrons(
+    mut neutrons: Query<
+        (
+            Entity,
+            Transform,
+            Mut<Velocity>,
+            Progenitor,
+            Option<CollideWithPlayer>,
+            Option<SourceNeutron>,
+            Lineage,
+            HitBudget,
+            Homing,
+        ),
+        (With<Neutron>, Without<Pooled>),
+    >,
+    lineage: Res<LineageConfig>,
+    mut budget: ResMut<EntityBudget>,
+    mut pool: ResMut<EntityPool>,
+    mut commands: Commands,
+) {
+    if !budget.over_soft_limit() {
+        return;
+    }
+
+    let radius = budget.merge_radius;
+    let cell = |transform: &Transform| {
+        (
+            (transform.translation.x / radius).floor() as i32,
+            (transform.translation.y / radius).floor() as i32,
+        )
+    };
+
+    let mut survivors = FxHashMap::<_, (Entity, usize)>::default();
+    let mut merged = FxHashMap::<Entity, Velocity>::default();
+    for (
+        entity,
+        transform,
+        velocity,
+        progenitor,
+        hits_player,
+        source,
+        neutron_lineage,
+        hit_budget,
+        homing,
+    ) in neutrons.iter()
+    {
+        // the player's shots are never merged away
+        if progenitor.0.is_none()
+            || *hit_budget != HitBudget::NONE
+            || *homing != Homing::NONE
+            || lineage.is_newborn(neutron_lineage)
+        {
+            continue;
+        }
+
+        let key = (
+            cell(transform),
+            hits_player.is_some(),
+            source.is_some(),
+            neutron_lineage.chain(),
+        );
+        match survivors.get_mut(&key) {
+            Some((survivor, count)) => {
+                *count += 1;
+                merged.entry(*survivor).or_insert(Velocity::default()).0 += velocity.0;
+                pool.release_neutron(entity, hits_player.is_some(), &mut commands);
+                budget.merged.insert(entity);
+            }
+            None => {
+                survivors.insert(key, (entity, 1));
+            }
+        }
+    }
+
+    for (survivor, count) in survivors.into_values().filter(|(_, c)| *c > 1) {
+        let Some((_, _, velocity, ..)) = neutrons.get_mut(survivor) else {
+            continue;
+        };
+        let others = merged.get(&survivor).copied().unwrap_or_default();
+        velocity.0 = (velocity.0 + others.0) * (1.0 / count as f32);
+    }
+
+    budget.neutrons = budget.neutrons.saturating_sub(budget.merged.len());
+}
+
+/// Despawns the oldest neutrons, then the latest fragments, until the budget is under the hard limit.
+fn enforce_hard_limit(
+    neutrons: Query<
+        (
+            Entity,
+            Uptime,
+            Progenitor,
+            Option<SourceNeutron>,
+            Option<CollideWithPlayer>,
+        ),
+        (With<Neutron>, Without<Pooled>),
+    >,
+    atoms: Query<(Entity, Events), (With<Atom>, Without<Pooled>)>,
+    budget: Res<EntityBudget>,
+    mut criticality: ResMut<Criticality>,
+    mut pool: ResMut<EntityPool>,
+    mut commands: Commands,
+) {
+    let mut excess = budget.total().saturating_sub(budget.hard_limit);
+    if excess == 0 {
+        return;
+    }
+
+    let mut oldest = neutrons
+        .iter()
+        .filter(|(entity, ..)| !budget.merged.contains(entity))
+        .collect::<Vec<_>>();
+    oldest.sort_by(|(_, a, ..), (_, b, ..)| b.0.total_cmp(&a.0));
+    for (entity, _, progenitor, source, hits_player) in oldest.into_iter().take(excess) {
+        pool.release_neutron(entity, hits_player.is_some(), &mut commands);
+        if progenitor.0.is_some() && source.is_none() {
+            criticality.record_losses(1);
+        }
+        excess -= 1;
+    }
+
+    let mut latest = atoms.iter().collect::<Vec<_>>();
+    latest.sort_by(|(_, a), (_, b)| b.0.cmp(&a.0));
+    for (entity, events) in latest.into_iter().take(excess) {
+        pool.release_atom(entity, events.0, &mut commands);
+    }
+}
@@ -0,0 +1,177 @@
+use super::{
+    spawner::{Lifespan, Uptime},
+    NeutronBundle,
+};
+use crate::{
+    pool::{EntityPool, Pooled},
+    shaders::materials::Modulated,
+    Velocity,
+};
+use std::f32::consts::TAU;
+use winny::{
+    asset::server::AssetServer,
+    math::vector::{Vec2f, Vec3f},
+    prelude::*,
+};
+
+/// How an entity with a [Lifespan] leaves, instead of disappearing the frame it expires.
+///
+/// Over the last `over` seconds of its lifespan the entity fades its material out and shrinks,
+/// and once it expires it can leave something behind.
+#[derive(Debug, Clone, Copy, PartialEq, Component)]
+pub struct Expiry {
+    /// Seconds before the end of the lifespan the entity starts fading or shrinking.
+    over: f32,
+    fade: bool,
+    shrink: bool,
+    spawn: Option<ExpirySpawn>,
+    /// The scale the entity shrinks from, taken when it starts shrinking.
+    from_scale: Option<Vec2f>,
+}
+
+impl Default for Expiry {
+    fn default() -> Self {
+        Self::NONE
+    }
+}
+
+impl Expiry {
+    pub const NONE: Self = Self {
+        over: 0.,
+        fade: false,
+        shrink: false,
+        spawn: None,
+        from_scale: None,
+    };
+
+    pub fn new(over: f32) -> Self {
+        Self { over, ..Self::NONE }
+    }
+
+    pub fn with_fade(mut self) -> Self {
+        self.fade = true;
+        self
+    }
+
+    pub fn with_shrink(mut self) -> Self {
+        self.shrink = true;
+        self
+    }
+
+    pub fn with_spawn(mut self, spawn: ExpirySpawn) -> Self {
+        self.spawn = Some(spawn);
+        self
+    }
+
+    pub fn spawn(&self) -> Option<ExpirySpawn> {
+        self.spawn
+    }
+
+    /// How much of the expiry is left, 1 before it starts and 0 once the lifespan is over.
+    pub fn remaining(&self, uptime: &Uptime, lifespan: &Lifespan) -> f32 {
+        ((lifespan.0 - uptime.0) / self.over.max(f32::EPSILON)).clamp(0., 1.)
+    }
+}
+
+/// What an [Expiry] leaves behind.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExpirySpawn {
+    /// A ring of neutrons.
+    Burst { count: u32, speed: f32 },
+    /// A neutron that sits still for `lifespan` seconds.
+    Mine { lifespan: f32 },
+    /// A one-shot sound.
+    Sound { path: &'static str, volume: f32 },
+}
+
+/// Sent by [bullet_lifetime](super::spawner::bullet_lifetime) for every expiring entity
+/// that leaves something behind.
+#[derive(Debug, Clone, Copy, Event)]
+pub struct Expired {
+    pub position: Vec3f,
+    pub spawn: ExpirySpawn,
+    /// Whether the expired entity could hit the player, so will what it leaves.
+    pub hits_player: bool,
+}
+
+/// Scales expiring entities down to nothing.
+///
+/// Colliders scale with the transform, so a shrinking projectile also gets harder to hit and
+/// to be hit by. This is intended, a projectile that is almost gone should not land a full hit.
+pub fn shrink_expiring(
+    mut expiring: Query<(Mut<Expiry>, Mut<Transform>, Uptime, Lifespan), Without<Pooled>>,
+) {
+    for (expiry, transform, uptime, lifespan) in expiring.iter_mut() {
+        if !expiry.shrink {
+            continue;
+        }
+
+        let remaining = expiry.remaining(uptime, lifespan);
+        if remaining >= 1. {
+            continue;
+        }
+
+        let from = *expiry.from_scale.get_or_insert(transform.scale);
+        transform.scale = Vec2f::new(from.x * remaining, from.y * remaining);
+    }
+}
+
+/// Fades the material of expiring entities, registered once for every material that fades.
+pub fn fade_expiring<M: Component + Modulated>(
+    mut expiring: Query<(Mut<M>, Expiry, Uptime, Lifespan), Without<Pooled>>,
+) {
+    for (material, expiry, uptime, lifespan) in expiring.iter_mut() {
+        if expiry.fade {
+            material.modulation_mut().0.w = expiry.remaining(uptime, lifespan);
+        }
+    }
+}
+
+pub fn spawn_on_expiry(
+    reader: EventReader<Expired>,
+    mut commands: Commands,
+    server: Res<AssetServer>,
+    mut pool: ResMut<EntityPool>,
+) {
+    for event in reader.peak_read() {
+        let transform = Transform {
+            translation: event.position,
+            ..Default::default()
+        };
+
+        match event.spawn {
+            ExpirySpawn::Burst { count, speed } => {
+                for i in 0..count {
+                    let direction = i as f32 / count as f32 * TAU;
+                    NeutronBundle::spawn(
+                        &server,
+                        transform,
+                        Velocity(Vec3f::new(direction.cos(), direction.sin(), 0.) * speed),
+                        None,
+                        event.hits_player,
+                        &mut commands,
+                        &mut pool,
+                    );
+                }
+            }
+            ExpirySpawn::Mine { lifespan } => {
+                let mine = NeutronBundle::spawn(
+                    &server,
+                    transform,
+                    Velocity(Vec3f::zero()),
+                    None,
+                    event.hits_player,
+                    &mut commands,
+                    &mut pool,
+                );
+                commands.get_entity(mine).insert(Lifespan(lifespan));
+            }
+            ExpirySpawn::Sound { path, volume } => {
+                commands.spawn(AudioBundle {
+                    handle: server.load(path),
+                    playback_settings: PlaybackSettings::default().with_volume(volume),
+                });
+            }
+        }
+    }
+}
@@ -0,0 +1,183 @@
+use super::spawner::Uptime;
+use crate::{
+    atoms::Atom,
+    enemy::{Heading, RegularEnemy, TurnSpeed},
+    player::Player,
+    pool::Pooled,
+    Velocity,
+};
+use winny::{math::vector::Vec3f, prelude::*};
+
+/// Steering for the player's own shots.
+#[derive(Debug, Resource, AsEgui)]
+pub struct HomingConfig {
+    /// Makes every weapon home, not just the ones that do already.
+    player_shots: bool,
+    /// Degrees per second.
+    turn_rate: f32,
+    /// Seconds a shot steers for before flying straight.
+    duration: f32,
+    range: f32,
+}
+
+impl Default for HomingConfig {
+    fn default() -> Self {
+        Self {
+            player_shots: false,
+            turn_rate: 270.,
+            duration: 1.,
+            range: 600.,
+        }
+    }
+}
+
+impl HomingConfig {
+    /// How the player's shots home, [Homing::NONE] if they don't.
+    pub fn player_homing(&self, weapon_homes: bool) -> (Homing, f32) {
+        if !self.player_shots && !weapon_homes {
+            return (Homing::NONE, 0.);
+        }
+
+        (
+            Homing::new(HomingTarget::NearestAtom, self.duration).with_range(self.range),
+            self.turn_rate.to_radians(),
+        )
+    }
+}
+
+/// What a homing projectile picks as its target.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HomingTarget {
+    NearestAtom,
+    NearestEnemy,
+    Player,
+}
+
+/// Steers a projectile towards a target with [Heading::steer_towards], turning at its
+/// [TurnSpeed].
+///
+/// The target is picked when the projectile is fired and again whenever it is lost. Neutrons
+/// always carry one so pooled neutrons can be reused, [Homing::NONE] doesn't steer.
+#[derive(Debug, Clone, Copy, PartialEq, Component)]
+pub struct Homing {
+    target: Option<HomingTarget>,
+    /// Seconds after firing the projectile stops steering.
+    duration: f32,
+    /// Targets further away than this are ignored.
+    range: f32,
+    locked: Option<Entity>,
+}
+
+impl Default for Homing {
+    fn default() -> Self {
+        Self::NONE
+    }
+}
+
+impl Homing {
+    pub const NONE: Self = Self {
+        target: None,
+        duration: 0.,
+        range: f32::INFINITY,
+        locked: None,
+    };
+
+    pub fn new(target: HomingTarget, duration: f32) -> Self {
+        Self {
+            target: Some(target),
+            duration,
+            ..Self::NONE
+        }
+    }
+
+    pub fn with_range(mut self, range: f32) -> Self {
+        self.range = range;
+        self
+    }
+
+    pub fn is_homing(&self) -> bool {
+        self.target.is_some()
+    }
+
+    /// Makes `projectile` home, `turn_rate` is in radians per second.
+    pub fn attach(self, projectile: Entity, turn_rate: f32, commands: &mut Commands) {
+        commands
+            .get_entity(projectile)
+            .insert(self)
+            .insert(TurnSpeed(turn_rate))
+            .insert(Heading::default());
+    }
+}
+
+pub fn steer_homing(
+    mut projectiles: Query<
+        (
+            Mut<Velocity>,
+            Mut<Heading>,
+            Mut<Homing>,
+            Transform,
+            TurnSpeed,
+            Uptime,
+        ),
+        Without<Pooled>,
+    >,
+    targets: Query<Transform, Without<Pooled>>,
+    atoms: Query<(Entity, Transform), (With<Atom>, Without<Pooled>)>,
+    enemies: Query<(Entity, Transform), With<RegularEnemy>>,
+    player: Query<(Entity, Transform), With<Player>>,
+    delta: Res<DeltaTime>,
+) {
+    for (velocity, heading, homing, transform, turn_speed, uptime) in projectiles.iter_mut() {
+        let Some(filter) = homing.target else {
+            continue;
+        };
+        if uptime.0 > homing.duration || velocity.0.is_zero() {
+            continue;
+        }
+
+        let from = transform.translation;
+        let mut target = homing
+            .locked
+            .and_then(|e| targets.get(e))
+            .map(|t| t.translation);
+
+        if target.is_none() {
+            homing.locked = match filter {
+                HomingTarget::NearestAtom => nearest(&from, homing.range, atoms.iter()),
+                HomingTarget::NearestEnemy => nearest(&from, homing.range, enemies.iter()),
+                HomingTarget::Player => nearest(&from, homing.range, player.iter()),
+            };
+            target = homing
+                .locked
+                .and_then(|e| targets.get(e))
+                .map(|t| t.translation);
+        }
+
+        let Some(target) = target else {
+            continue;
+        };
+        if target == from {
+            continue;
+        }
+
+        // the velocity may have been changed by anything, so that is the source of truth
+        let speed = velocity.0.magnitude();
+        heading.direction.0 = velocity.0.y.atan2(velocity.0.x);
+        heading.steer_towards(turn_speed.0 * delta.delta, &from, &target);
+
+        velocity.0 = Vec3f::new(heading.direction.0.cos(), heading.direction.0.sin(), 0.) * speed;
+    }
+}
+
+fn nearest<'a>(
+    from: &Vec3f,
+    range: f32,
+    candidates: impl Iterator<Item = (Entity, &'a Transform)>,
+) -> Option<Entity> {
+    let range = range * range;
+    candidates
+        .map(|(e, t)| (e, t.translation.dist2(from)))
+        .filter(|(_, d)| *d <= range)
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(e, _)| e)
+}
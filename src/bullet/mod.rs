@@ -1,4 +1,10 @@
-use self::spawner::{BulletSpawner, Lifespan, Uptime};
+use self::{
+    expiry::Expiry,
+    homing::Homing,
+    pattern::{BulletPattern, SpeedCurve},
+    pierce::HitBudget,
+    spawner::{BulletSpawner, Lifespan, Uptime},
+};
 use std::f32::consts::PI;
 
 use crate::{
@@ -6,6 +12,9 @@ use crate::{
     collision::{
         CircleCollider, CollideWithEnemy, CollideWithPlayer, Collider, RemoveOnPlayerCollision,
     },
+    enemy::{Heading, TurnSpeed},
+    lineage::Lineage,
+    pool::{EntityPool, Pooled},
     shaders::{materials::NeutronMaterial, SpaceHaze},
     CollisionDamage, Velocity,
 };
@@ -22,6 +31,11 @@ use winny::{
     prelude::*,
 };
 
+pub mod expiry;
+pub mod homing;
+pub mod pattern;
+pub mod pattern_file;
+pub mod pierce;
 pub mod spawner;
 
 // #[derive(Bundle)]
@@ -165,8 +179,27 @@ pub mod spawner;
 #[derive(Component, Clone, Copy, PartialEq, Eq)]
 pub struct Progenitor(pub Option<Entity>);
 
+pub const NEUTRON_DAMAGE: f32 = 1.;
+pub const NEUTRON_LIFESPAN: f32 = 4.;
+/// Seconds neutrons take to fade out before their lifespan ends.
+const NEUTRON_FADE: f32 = 0.5;
+
+/// Extra neutrons released by the fission a neutron causes.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Component)]
+pub struct ExtraYield(pub usize);
+
+/// Neutron marker struct.
+#[derive(Debug, Clone, Copy, Component)]
+pub struct Neutron;
+
+/// Marks projectiles fired by enemies, as opposed to fission neutrons that can also hit the
+/// player.
+#[derive(Debug, Clone, Copy, Component)]
+pub struct EnemyProjectile;
+
 #[derive(Bundle)]
 pub struct NeutronBundle {
+    neutron: Neutron,
     transform: Transform,
     velocity: Velocity,
     collider: Collider,
@@ -178,6 +211,14 @@ pub struct NeutronBundle {
     material: NeutronMaterial,
     radial_velocity: RadialVelocity,
     progenitor: Progenitor,
+    lineage: Lineage,
+    curve: SpeedCurve,
+    homing: Homing,
+    heading: Heading,
+    turn_speed: TurnSpeed,
+    hit_budget: HitBudget,
+    extra_yield: ExtraYield,
+    expiry: Expiry,
 }
 
 impl NeutronBundle {
@@ -188,10 +229,32 @@ impl NeutronBundle {
         progenitor: Option<Entity>,
         hit_player: bool,
         commands: &mut Commands,
-    ) {
+        pool: &mut EntityPool,
+    ) -> Entity {
         transform.scale = Vec2f::new(0.1, 0.1);
 
+        if let Some(entity) = pool.take_neutron(hit_player) {
+            commands
+                .get_entity(entity)
+                .remove::<Pooled>()
+                .insert(transform)
+                .insert(velocity)
+                .insert(Uptime(0f32))
+                .insert(Lifespan(NEUTRON_LIFESPAN))
+                .insert(Self::material())
+                .insert(Self::expiry())
+                .insert(Progenitor(progenitor))
+                .insert(Lineage::default())
+                .insert(SpeedCurve::CONSTANT)
+                .insert(Homing::NONE)
+                .insert(HitBudget::NONE)
+                .insert(ExtraYield(0))
+                .insert(CollisionDamage(NEUTRON_DAMAGE));
+            return entity;
+        }
+
         let bundle = Self {
+            neutron: Neutron,
             transform,
             velocity,
             collider: Collider::Circle(CircleCollider {
@@ -199,46 +262,60 @@ impl NeutronBundle {
                 radius: 30f32,
             }),
             collides: CollideWithEnemy,
-            damage: CollisionDamage(1.),
-            lifespan: Lifespan(4f32),
+            damage: CollisionDamage(NEUTRON_DAMAGE),
+            lifespan: Lifespan(NEUTRON_LIFESPAN),
             uptime: Uptime(0f32),
             mesh: server.load("res/saved/bullet_1_mesh.msh"),
-            material: NeutronMaterial {
-                modulation: Modulation(SpaceHaze::pink()),
-            },
+            material: Self::material(),
             radial_velocity: RadialVelocity {
                 strength: Radf(1.0),
                 total_rotation: Radf(0.0),
             },
             progenitor: Progenitor(progenitor),
+            lineage: Lineage::default(),
+            curve: SpeedCurve::CONSTANT,
+            homing: Homing::NONE,
+            heading: Heading::default(),
+            turn_speed: TurnSpeed::default(),
+            hit_budget: HitBudget::NONE,
+            extra_yield: ExtraYield(0),
+            expiry: Self::expiry(),
         };
 
         if hit_player {
-            commands.spawn((bundle, CollideWithPlayer, RemoveOnPlayerCollision));
+            commands
+                .spawn((bundle, CollideWithPlayer, RemoveOnPlayerCollision))
+                .entity()
         } else {
-            commands.spawn(bundle);
+            commands.spawn(bundle).entity()
         }
     }
 
+    /// Reinserted on reuse, pooled neutrons may have faded out.
+    fn material() -> NeutronMaterial {
+        NeutronMaterial {
+            modulation: Modulation(SpaceHaze::pink()),
+        }
+    }
+
+    pub fn expiry() -> Expiry {
+        Expiry::new(NEUTRON_FADE).with_fade().with_shrink()
+    }
+
     pub fn spawn_audio_bundle(_audio_master: &mut AudioMaster) {
         // let sound_path = AudioPath("04_Fire_explosion_04_medium.wav");
         // audio_master.queue_new_bundle(sound_path, PlaybackSettings::default().with_volume(4.0));
     }
 
     pub fn new_spawner() -> BulletSpawner {
-        BulletSpawner::new(0.5, move |transform, commands, server, audio_master| {
-            const SPEED: f32 = 1.5;
-            for i in 0..4 {
-                let direction = i as f32 * 0.5 * PI + 0.25 * PI;
-                let x = direction.cos() * SPEED;
-                let y = direction.sin() * SPEED;
-                let velocity = Velocity(Vec3f::new(x, y, 0.));
-                // commands.spawn(Self::new(server, *transform, velocity, None));
-
-                Self::spawn(server, *transform, velocity, None, true, commands);
-            }
-            Self::spawn_audio_bundle(audio_master);
-        })
+        BulletSpawner::from_pattern(
+            0.5,
+            BulletPattern::Ring {
+                count: 4,
+                speed: 1.5,
+                angle: 0.25 * PI,
+            },
+        )
     }
 }
 
@@ -0,0 +1,305 @@
+use super::spawner::BulletSpawnerFn;
+use std::{f32::consts::TAU, sync::Arc};
+use winny::{math::vector::Vec3f, prelude::*};
+
+/// Where a pattern points.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Aim {
+    /// Relative to the direction of the enclosing pattern, in radians.
+    Angle(f32),
+    /// At the player, if there is one.
+    Player,
+}
+
+/// A declarative description of the bullets a [BulletSpawner](super::spawner::BulletSpawner) fires.
+///
+/// Angles are in radians, speeds in pixels per frame and delays in seconds. Each time the
+/// spawner triggers is a volley, patterns that turn use the volley count to do so.
+#[derive(Clone)]
+pub enum BulletPattern {
+    /// `count` bullets spread evenly around a circle, starting at `angle`.
+    Ring { count: u32, speed: f32, angle: f32 },
+    /// A ring of `arms` bullets that turns by `turn` every volley.
+    Spiral { arms: u32, speed: f32, turn: f32 },
+    /// `count` bullets spread across `spread`, centered on `aim`.
+    Fan {
+        count: u32,
+        spread: f32,
+        speed: f32,
+        aim: Aim,
+    },
+    /// Fires `pattern` `count` times, `delay` apart.
+    Burst {
+        count: u32,
+        delay: f32,
+        pattern: Arc<BulletPattern>,
+    },
+    /// Turns `pattern` by `turn` every volley.
+    Rotating {
+        turn: f32,
+        pattern: Arc<BulletPattern>,
+    },
+    /// Fires `pattern` after `delay`.
+    Delay {
+        delay: f32,
+        pattern: Arc<BulletPattern>,
+    },
+    /// Fires every pattern at once.
+    Nested(Vec<Arc<BulletPattern>>),
+    /// Calls a closure for anything the patterns can't describe.
+    Custom(BulletSpawnerFn),
+}
+
+impl std::fmt::Debug for BulletPattern {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Ring {
+                count,
+                speed,
+                angle,
+            } => f
+                .debug_struct("Ring")
+                .field("count", count)
+                .field("speed", speed)
+                .field("angle", angle)
+                .finish(),
+            Self::Spiral { arms, speed, turn } => f
+                .debug_struct("Spiral")
+                .field("arms", arms)
+                .field("speed", speed)
+                .field("turn", turn)
+                .finish(),
+            Self::Fan {
+                count,
+                spread,
+                speed,
+                aim,
+            } => f
+                .debug_struct("Fan")
+                .field("count", count)
+                .field("spread", spread)
+                .field("speed", speed)
+                .field("aim", aim)
+                .finish(),
+            Self::Burst {
+                count,
+                delay,
+                pattern,
+            } => f
+                .debug_struct("Burst")
+                .field("count", count)
+                .field("delay", delay)
+                .field("pattern", pattern)
+                .finish(),
+            Self::Rotating { turn, pattern } => f
+                .debug_struct("Rotating")
+                .field("turn", turn)
+                .field("pattern", pattern)
+                .finish(),
+            Self::Delay { delay, pattern } => f
+                .debug_struct("Delay")
+                .field("delay", delay)
+                .field("pattern", pattern)
+                .finish(),
+            Self::Nested(patterns) => f.debug_tuple("Nested").field(patterns).finish(),
+            Self::Custom(_) => f.write_str("Custom"),
+        }
+    }
+}
+
+/// What the interpreter asks the spawner to do.
+pub enum Emission {
+    /// Fire a bullet in `direction` radians.
+    Shot {
+        direction: f32,
+        speed: f32,
+    },
+    /// Fire `pattern` after `delay`, pointing at `base`.
+    Later {
+        delay: f32,
+        base: f32,
+        pattern: Arc<BulletPattern>,
+    },
+    Custom(BulletSpawnerFn),
+}
+
+/// What a spawner fires.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Projectile {
+    #[default]
+    Neutron,
+    /// Radioactive [Debris](crate::debris::Debris), always hurts the player.
+    Debris,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Ease {
+    #[default]
+    Linear,
+    In,
+    Out,
+    InOut,
+}
+
+impl Ease {
+    pub fn apply(&self, t: f32) -> f32 {
+        let t = t.clamp(0., 1.);
+        match self {
+            Self::Linear => t,
+            Self::In => t * t,
+            Self::Out => 1. - (1. - t) * (1. - t),
+            Self::InOut => t * t * (3. - 2. * t),
+        }
+    }
+}
+
+/// Scales the speed of a bullet over its lifetime.
+///
+/// The speed is multiplied by `from` when the bullet is fired and eases to `to` over `over`
+/// seconds. Applied relative to the last frame, so anything else that changes the velocity,
+/// like a [Moderator](crate::layout::Moderator), still does.
+#[derive(Debug, Clone, Copy, PartialEq, Component)]
+pub struct SpeedCurve {
+    pub from: f32,
+    pub to: f32,
+    pub over: f32,
+    pub ease: Ease,
+}
+
+impl Default for SpeedCurve {
+    fn default() -> Self {
+        Self::CONSTANT
+    }
+}
+
+impl SpeedCurve {
+    pub const CONSTANT: Self = Self {
+        from: 1.,
+        to: 1.,
+        over: 0.,
+        ease: Ease::Linear,
+    };
+
+    /// Multipliers are kept away from zero so a bullet never loses its direction.
+    const MIN_SCALE: f32 = 0.01;
+
+    pub fn is_constant(&self) -> bool {
+        self.from == self.to || self.over <= 0.
+    }
+
+    /// The speed multiplier `uptime` seconds after the bullet was fired.
+    pub fn at(&self, uptime: f32) -> f32 {
+        let t = if self.over > 0. {
+            uptime / self.over
+        } else {
+            1.
+        };
+        let scale = self.from + (self.to - self.from) * self.ease.apply(t);
+        scale.max(Self::MIN_SCALE)
+    }
+}
+
+/// Everything a pattern needs to know to fire.
+#[derive(Debug, Clone, Copy)]
+pub struct PatternContext {
+    pub origin: Vec3f,
+    pub target: Option<Vec3f>,
+    /// The direction of the enclosing pattern, in radians.
+    pub base: f32,
+    pub volley: u32,
+}
+
+impl BulletPattern {
+    /// Interprets the pattern, bullets and delayed sub patterns are handed to `emit`.
+    pub fn fire(&self, context: PatternContext, emit: &mut impl FnMut(Emission)) {
+        match self {
+            Self::Ring {
+                count,
+                speed,
+                angle,
+            } => ring(*count, *speed, context.base + angle, emit),
+            Self::Spiral { arms, speed, turn } => ring(
+                *arms,
+                *speed,
+                context.base + turn * context.volley as f32,
+                emit,
+            ),
+            Self::Fan {
+                count,
+                spread,
+                speed,
+                aim,
+            } => {
+                let center = match aim {
+                    Aim::Angle(angle) => context.base + angle,
+                    Aim::Player => context
+                        .target
+                        .map(|t| t - context.origin)
+                        .filter(|d| !d.is_zero())
+                        .map_or(context.base, |d| d.y.atan2(d.x)),
+                };
+
+                if *count <= 1 {
+                    emit(Emission::Shot {
+                        direction: center,
+                        speed: *speed,
+                    });
+                    return;
+                }
+
+                let step = spread / (count - 1) as f32;
+                for i in 0..*count {
+                    emit(Emission::Shot {
+                        direction: center - spread * 0.5 + step * i as f32,
+                        speed: *speed,
+                    });
+                }
+            }
+            Self::Burst {
+                count,
+                delay,
+                pattern,
+            } => {
+                if *count == 0 {
+                    return;
+                }
+
+                pattern.fire(context, emit);
+                for i in 1..*count {
+                    emit(Emission::Later {
+                        delay: delay * i as f32,
+                        base: context.base,
+                        pattern: Arc::clone(pattern),
+                    });
+                }
+            }
+            Self::Rotating { turn, pattern } => pattern.fire(
+                PatternContext {
+                    base: context.base + turn * context.volley as f32,
+                    ..context
+                },
+                emit,
+            ),
+            Self::Delay { delay, pattern } => emit(Emission::Later {
+                delay: *delay,
+                base: context.base,
+                pattern: Arc::clone(pattern),
+            }),
+            Self::Nested(patterns) => {
+                for pattern in patterns.iter() {
+                    pattern.fire(context, emit);
+                }
+            }
+            Self::Custom(spawner) => emit(Emission::Custom(Arc::clone(spawner))),
+        }
+    }
+}
+
+fn ring(count: u32, speed: f32, start: f32, emit: &mut impl FnMut(Emission)) {
+    for i in 0..count {
+        emit(Emission::Shot {
+            direction: start + i as f32 * TAU / count as f32,
+            speed,
+        });
+    }
+}
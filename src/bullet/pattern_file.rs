@@ -0,0 +1,396 @@
+//! Bullet patterns described in TOML, see `res/patterns` for examples.
+//!
+//! Angles in pattern files are in degrees, everything else uses the units of [BulletPattern].
+//! ```toml
+//! period = 0.5
+//! projectile = "neutron"
+//!
+//! [curve]
+//! from = 2.0
+//! to = 0.5
+//! over = 1.0
+//! ease = "out"
+//!
+//! [pattern]
+//! shape = "burst"
+//! count = 3
+//! delay = 0.1
+//!
+//! [pattern.pattern]
+//! shape = "fan"
+//! count = 5
+//! spread = 60.0
+//! speed = 1.5
+//! aim = "player"
+//!
+//! [homing]
+//! target = "nearest_atom"
+//! turn_rate = 120.0
+//! duration = 2.0
+//!
+//! [pierce]
+//! hits = 2
+//! falloff = 0.5
+//! ricochet = true
+//! ```
+//! The files in `res/patterns` are bundled into the binary as [BundledPattern]s. On native
+//! builds they are also loaded as [Toml] assets and reloaded by the `WatcherPlugin` when they
+//! change, see [PatternSource].
+
+use super::{
+    homing::{Homing, HomingTarget},
+    pattern::{Aim, BulletPattern, Ease, Projectile, SpeedCurve},
+    pierce::HitBudget,
+    spawner::BulletSpawner,
+};
+use serde::Deserialize;
+use std::sync::Arc;
+use winny::{asset::server::AssetServer, prelude::*};
+
+/// Spawners fire at least this far apart, a period of zero would fire every frame.
+const MIN_PERIOD: f32 = 0.05;
+
+/// The contents of a pattern file.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PatternFile {
+    /// Seconds between volleys.
+    period: f32,
+    #[serde(default = "hits_player")]
+    hits_player: bool,
+    #[serde(default)]
+    projectile: ProjectileFile,
+    #[serde(default)]
+    curve: Option<CurveFile>,
+    #[serde(default)]
+    homing: Option<HomingFile>,
+    #[serde(default)]
+    pierce: Option<PierceFile>,
+    pattern: PatternShape,
+}
+
+fn hits_player() -> bool {
+    true
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum ProjectileFile {
+    #[default]
+    Neutron,
+    Debris,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CurveFile {
+    #[serde(default = "unit")]
+    from: f32,
+    #[serde(default = "unit")]
+    to: f32,
+    over: f32,
+    #[serde(default)]
+    ease: EaseFile,
+}
+
+fn unit() -> f32 {
+    1.
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum EaseFile {
+    #[default]
+    Linear,
+    In,
+    Out,
+    InOut,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct HomingFile {
+    target: HomingTargetFile,
+    /// Degrees per second.
+    turn_rate: f32,
+    duration: f32,
+    #[serde(default)]
+    range: Option<f32>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum HomingTargetFile {
+    NearestAtom,
+    NearestEnemy,
+    Player,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PierceFile {
+    /// How many hits a bullet survives.
+    hits: u32,
+    #[serde(default = "unit")]
+    falloff: f32,
+    #[serde(default)]
+    ricochet: bool,
+}
+
+/// Either an angle in degrees or a target.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum AimFile {
+    Angle(f32),
+    Target(AimTarget),
+}
+
+impl Default for AimFile {
+    fn default() -> Self {
+        Self::Angle(0.)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum AimTarget {
+    Player,
+}
+
+/// Mirrors [BulletPattern], minus closures.
+#[derive(Debug, Deserialize)]
+#[serde(tag = "shape", rename_all = "snake_case")]
+enum PatternShape {
+    Ring {
+        count: u32,
+        speed: f32,
+        #[serde(default)]
+        angle: f32,
+    },
+    Spiral {
+        arms: u32,
+        speed: f32,
+        turn: f32,
+    },
+    Fan {
+        count: u32,
+        spread: f32,
+        speed: f32,
+        #[serde(default)]
+        aim: AimFile,
+    },
+    Burst {
+        count: u32,
+        delay: f32,
+        pattern: Box<PatternShape>,
+    },
+    Rotating {
+        turn: f32,
+        pattern: Box<PatternShape>,
+    },
+    Delay {
+        delay: f32,
+        pattern: Box<PatternShape>,
+    },
+    Nested {
+        patterns: Vec<PatternShape>,
+    },
+}
+
+impl PatternShape {
+    fn into_pattern(self) -> BulletPattern {
+        match self {
+            Self::Ring {
+                count,
+                speed,
+                angle,
+            } => BulletPattern::Ring {
+                count,
+                speed,
+                angle: angle.to_radians(),
+            },
+            Self::Spiral { arms, speed, turn } => BulletPattern::Spiral {
+                arms,
+                speed,
+                turn: turn.to_radians(),
+            },
+            Self::Fan {
+                count,
+                spread,
+                speed,
+                aim,
+            } => BulletPattern::Fan {
+                count,
+                spread: spread.to_radians(),
+                speed,
+                aim: match aim {
+                    AimFile::Angle(angle) => Aim::Angle(angle.to_radians()),
+                    AimFile::Target(AimTarget::Player) => Aim::Player,
+                },
+            },
+            Self::Burst {
+                count,
+                delay,
+                pattern,
+            } => BulletPattern::Burst {
+                count,
+                delay: delay.max(0.),
+                pattern: Arc::new(pattern.into_pattern()),
+            },
+            Self::Rotating { turn, pattern } => BulletPattern::Rotating {
+                turn: turn.to_radians(),
+                pattern: Arc::new(pattern.into_pattern()),
+            },
+            Self::Delay { delay, pattern } => BulletPattern::Delay {
+                delay: delay.max(0.),
+                pattern: Arc::new(pattern.into_pattern()),
+            },
+            Self::Nested { patterns } => BulletPattern::Nested(
+                patterns
+                    .into_iter()
+                    .map(|p| Arc::new(p.into_pattern()))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+impl PatternFile {
+    pub fn parse(source: &str) -> Result<Self, toml::de::Error> {
+        toml::from_str(source)
+    }
+
+    pub fn into_spawner(self) -> BulletSpawner {
+        let curve = self.curve.map_or(SpeedCurve::CONSTANT, |c| SpeedCurve {
+            from: c.from,
+            to: c.to,
+            over: c.over,
+            ease: match c.ease {
+                EaseFile::Linear => Ease::Linear,
+                EaseFile::In => Ease::In,
+                EaseFile::Out => Ease::Out,
+                EaseFile::InOut => Ease::InOut,
+            },
+        });
+
+        let (homing, turn_rate) = self.homing.map_or((Homing::NONE, 0.), |h| {
+            let target = match h.target {
+                HomingTargetFile::NearestAtom => HomingTarget::NearestAtom,
+                HomingTargetFile::NearestEnemy => HomingTarget::NearestEnemy,
+                HomingTargetFile::Player => HomingTarget::Player,
+            };
+            let homing = Homing::new(target, h.duration);
+            (
+                h.range.map_or(homing, |r| homing.with_range(r)),
+                h.turn_rate.to_radians(),
+            )
+        });
+
+        BulletSpawner::from_pattern(self.period.max(MIN_PERIOD), self.pattern.into_pattern())
+            .with_hits_player(self.hits_player)
+            .with_projectile(match self.projectile {
+                ProjectileFile::Neutron => Projectile::Neutron,
+                ProjectileFile::Debris => Projectile::Debris,
+            })
+            .with_curve(curve)
+            .with_homing(homing, turn_rate)
+            .with_hit_budget(self.pierce.map_or(HitBudget::NONE, |p| {
+                HitBudget::new(p.hits, p.falloff).with_ricochet(p.ricochet)
+            }))
+    }
+}
+
+/// A pattern file from `res/patterns`, included in the binary so it also works on the web.
+#[derive(Debug, Clone, Copy)]
+pub struct BundledPattern {
+    path: &'static str,
+    source: &'static str,
+}
+
+macro_rules! bundled_pattern {
+    ($file:literal) => {
+        BundledPattern {
+            path: concat!("res/patterns/", $file),
+            source: include_str!(concat!("../../res/patterns/", $file)),
+        }
+    };
+}
+
+impl BundledPattern {
+    pub const DEBRIS_RING: Self = bundled_pattern!("debris_ring.toml");
+    pub const FAN_BURST: Self = bundled_pattern!("fan_burst.toml");
+    /// Fired by turret satellites.
+    pub const RICOCHET: Self = bundled_pattern!("ricochet.toml");
+    pub const SEEKERS: Self = bundled_pattern!("seekers.toml");
+    pub const SPIRAL: Self = bundled_pattern!("spiral.toml");
+
+    /// Panics if the bundled file does not parse, it would have failed on startup anyway.
+    pub fn spawner(&self) -> BulletSpawner {
+        PatternFile::parse(self.source)
+            .unwrap_or_else(|e| panic!("failed to parse bullet pattern [{}]: {e}", self.path))
+            .into_spawner()
+    }
+
+    /// Gives `entity` a spawner firing this pattern, on native builds it follows the file on
+    /// disk.
+    #[allow(unused_variables)]
+    pub fn attach(&self, entity: Entity, server: &AssetServer, commands: &mut Commands) {
+        commands.get_entity(entity).insert(self.spawner());
+        #[cfg(not(target_arch = "wasm32"))]
+        commands
+            .get_entity(entity)
+            .insert(PatternSource::load(self.path, server));
+    }
+}
+
+/// The file a [BulletSpawner] reads its pattern from.
+///
+/// The spawner keeps its last good pattern if an edit fails to parse.
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug, Component)]
+pub struct PatternSource {
+    path: String,
+    handle: Handle<Toml>,
+    /// Hash of the source the spawner was last loaded from.
+    loaded: Option<u64>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl PatternSource {
+    /// The source for a [BulletSpawner] on the same entity.
+    pub fn load(path: &str, server: &AssetServer) -> Self {
+        Self {
+            path: path.into(),
+            handle: server.load::<Toml, _>(path),
+            loaded: None,
+        }
+    }
+}
+
+/// Reloads the pattern of every spawner whose file changed.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn reload_patterns(
+    mut spawners: Query<(Mut<BulletSpawner>, Mut<PatternSource>)>,
+    files: Res<Assets<Toml>>,
+) {
+    for (spawner, source) in spawners.iter_mut() {
+        let Some(file) = files.get(&source.handle) else {
+            continue;
+        };
+
+        let text = file.source();
+        let hash = fxhash::hash64(text);
+        if source.loaded == Some(hash) {
+            continue;
+        }
+        source.loaded = Some(hash);
+
+        match PatternFile::parse(text) {
+            Ok(pattern) => {
+                spawner.reload(pattern.into_spawner());
+                info!("loaded bullet pattern [{}]", source.path);
+            }
+            Err(e) => warn!("failed to parse bullet pattern [{}]: {e}", source.path),
+        }
+    }
+}
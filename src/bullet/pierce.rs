@@ -0,0 +1,108 @@
+use crate::{CollisionDamage, Velocity};
+use winny::{math::vector::Vec3f, prelude::*};
+
+/// How many hits a projectile survives.
+///
+/// Every hit costs a pierce and multiplies the projectile's damage by `falloff`, a projectile
+/// with none left is removed on its next hit. Ricocheting
+/// projectiles bounce off what they hit, and off deployed control rods instead of being
+/// absorbed. Neutrons always carry one so pooled neutrons can be reused,
+/// [HitBudget::NONE] is removed on the first hit.
+#[derive(Debug, Clone, Copy, PartialEq, Component)]
+pub struct HitBudget {
+    pierces: u32,
+    falloff: f32,
+    ricochet: bool,
+}
+
+impl Default for HitBudget {
+    fn default() -> Self {
+        Self::NONE
+    }
+}
+
+impl HitBudget {
+    pub const NONE: Self = Self {
+        pierces: 0,
+        falloff: 1.,
+        ricochet: false,
+    };
+
+    pub fn new(pierces: u32, falloff: f32) -> Self {
+        Self {
+            pierces,
+            falloff,
+            ricochet: false,
+        }
+    }
+
+    pub fn with_ricochet(mut self, ricochet: bool) -> Self {
+        self.ricochet = ricochet;
+        self
+    }
+
+    /// Lets the projectile survive `pierces` more hits.
+    pub fn with_extra_pierces(mut self, pierces: u32) -> Self {
+        self.pierces += pierces;
+        self
+    }
+
+    pub fn pierces(&self) -> u32 {
+        self.pierces
+    }
+
+    pub fn ricochets(&self) -> bool {
+        self.ricochet
+    }
+
+    /// Whether the next hit bounces the projectile off instead of removing it.
+    pub fn bounces(&self) -> bool {
+        self.ricochet && self.pierces > 0
+    }
+
+    /// Spends a hit on `projectile`, returns false if it had none left and should be removed.
+    ///
+    /// The projectile does less damage afterwards, and bounces off `obstacle` if it ricochets.
+    /// `velocity` and `damage` are updated too, so a projectile can be hit several times a frame.
+    pub fn spend(
+        &mut self,
+        projectile: Entity,
+        position: Vec3f,
+        velocity: &mut Vec3f,
+        damage: &mut f32,
+        obstacle: Vec3f,
+        commands: &mut Commands,
+    ) -> bool {
+        if self.pierces == 0 {
+            return false;
+        }
+        self.pierces -= 1;
+        *damage *= self.falloff;
+
+        commands
+            .get_entity(projectile)
+            .insert(*self)
+            .insert(CollisionDamage(*damage));
+        if self.ricochet {
+            *velocity = reflect(*velocity, position - obstacle);
+            commands.get_entity(projectile).insert(Velocity(*velocity));
+        }
+
+        true
+    }
+}
+
+/// Reflects `velocity` off a surface facing `normal`, unless it is already moving away.
+pub fn reflect(velocity: Vec3f, normal: Vec3f) -> Vec3f {
+    if normal.is_zero() {
+        return velocity * -1.;
+    }
+
+    let normal = normal.normalize();
+    let along = velocity.x * normal.x + velocity.y * normal.y;
+    if along >= 0. {
+        return velocity;
+    }
+
+    velocity - normal * (2. * along)
+}
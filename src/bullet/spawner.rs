@@ -1,5 +1,22 @@
-use super::RadialVelocity;
-use crate::{audio::AudioMaster, collision::EnemyCollideEvent, should_run_game};
+use super::{
+    expiry::{fade_expiring, shrink_expiring, spawn_on_expiry, Expired, Expiry},
+    homing::{steer_homing, Homing, HomingConfig},
+    pattern::{BulletPattern, Emission, PatternContext, Projectile, SpeedCurve},
+    pierce::HitBudget,
+    EnemyProjectile, Neutron, NeutronBundle, Progenitor, RadialVelocity,
+};
+use crate::{
+    audio::AudioMaster,
+    collision::{CollideWithPlayer, EnemyCollideEvent},
+    debris::DebrisBundle,
+    player::Player,
+    pool::{EntityPool, Pooled},
+    reactor::{Criticality, SourceNeutron},
+    regular::RegularPolygons,
+    shaders::materials::{HeptaMaterial, NeutronMaterial},
+    should_run_game, CollisionDamage, Enemy, Velocity,
+};
+use fxhash::FxHashMap;
 use std::sync::Arc;
 use winny::{
     asset::server::AssetServer, ecs::sets::IntoSystemStorage, math::vector::Vec3f, prelude::*,
@@ -23,11 +40,33 @@ impl Plugin for WeaponPlugin {
                     .run_if(should_run_game),
             );
         app.register_timer::<BulletEvent>()
+            .register_event::<Expired>()
+            .insert_resource(HomingConfig::default())
+            .egui_resource::<HomingConfig>()
             .add_systems(
                 Schedule::Update,
-                (initial_emit_bullet, bullet_timer, bullet_lifetime).run_if(should_run_game),
+                (
+                    initial_emit_bullet,
+                    bullet_timer,
+                    bullet_lifetime,
+                    apply_speed_curves,
+                    steer_homing,
+                    shrink_expiring,
+                    fade_expiring::<NeutronMaterial>,
+                    fade_expiring::<HeptaMaterial>,
+                )
+                    .run_if(should_run_game),
             )
-            .add_systems(Schedule::PostUpdate, bullet_remover);
+            .add_systems(
+                Schedule::PostUpdate,
+                (bullet_remover, spawn_on_expiry.run_if(should_run_game)),
+            );
+
+        #[cfg(not(target_arch = "wasm32"))]
+        app.add_systems(
+            Schedule::PreUpdate,
+            super::pattern_file::reload_patterns.run_if(should_run_game),
+        );
     }
 }
 
@@ -54,41 +93,115 @@ pub struct Lifespan(pub f32);
 #[derive(Debug, Component, Copy, Clone, PartialEq)]
 pub struct Uptime(pub f32);
 
-pub type BulletSpawnerFn =
-    Arc<dyn Fn(&Transform, &mut Commands, &AssetServer, &mut AudioMaster) + Send + Sync>;
+pub type BulletSpawnerFn = Arc<
+    dyn Fn(&Transform, &mut Commands, &AssetServer, &mut AudioMaster, &mut EntityPool)
+        + Send
+        + Sync,
+>;
 
 #[derive(Event)]
 pub struct BulletEvent {
     trigger: Entity,
-    spawner: BulletSpawnerFn,
+    pattern: Arc<BulletPattern>,
+    /// The direction the pattern points, in radians.
+    base: f32,
+    volley: u32,
     retrigger: bool,
 }
 
 #[derive(Component)]
 pub struct BulletSpawner {
-    spawner: BulletSpawnerFn,
+    pattern: Arc<BulletPattern>,
     spawn_period: f32,
     has_emitted: bool,
+    /// Whether the bullets can hit the player.
+    hits_player: bool,
+    projectile: Projectile,
+    curve: SpeedCurve,
+    homing: Homing,
+    /// Radians per second.
+    turn_rate: f32,
+    hit_budget: HitBudget,
 }
 
 impl BulletSpawner {
     pub fn new<F>(period: f32, spawner: F) -> Self
     where
-        F: Fn(&Transform, &mut Commands, &AssetServer, &mut AudioMaster) + Send + Sync + 'static,
+        F: Fn(&Transform, &mut Commands, &AssetServer, &mut AudioMaster, &mut EntityPool)
+            + Send
+            + Sync
+            + 'static,
     {
+        Self::from_pattern(period, BulletPattern::Custom(Arc::new(spawner)))
+    }
+
+    pub fn from_pattern(period: f32, pattern: BulletPattern) -> Self {
         Self {
-            spawner: Arc::new(spawner),
+            pattern: Arc::new(pattern),
             spawn_period: period,
             has_emitted: false,
+            hits_player: true,
+            projectile: Projectile::Neutron,
+            curve: SpeedCurve::CONSTANT,
+            homing: Homing::NONE,
+            turn_rate: 0.,
+            hit_budget: HitBudget::NONE,
         }
     }
 
+    pub fn with_hits_player(mut self, hits_player: bool) -> Self {
+        self.hits_player = hits_player;
+        self
+    }
+
+    pub fn with_projectile(mut self, projectile: Projectile) -> Self {
+        self.projectile = projectile;
+        self
+    }
+
+    pub fn with_curve(mut self, curve: SpeedCurve) -> Self {
+        self.curve = curve;
+        self
+    }
+
+    /// Makes the bullets home, `turn_rate` is in radians per second.
+    pub fn with_homing(mut self, homing: Homing, turn_rate: f32) -> Self {
+        self.homing = homing;
+        self.turn_rate = turn_rate;
+        self
+    }
+
+    /// Lets neutrons pierce, debris never hits enemies.
+    pub fn with_hit_budget(mut self, hit_budget: HitBudget) -> Self {
+        self.hit_budget = hit_budget;
+        self
+    }
+
+    /// Takes the pattern and timing of `other`, volleys that are already scheduled finish
+    /// with the old pattern.
+    pub fn reload(&mut self, other: BulletSpawner) {
+        self.pattern = other.pattern;
+        self.spawn_period = other.spawn_period;
+        self.hits_player = other.hits_player;
+        self.projectile = other.projectile;
+        self.curve = other.curve;
+        self.homing = other.homing;
+        self.turn_rate = other.turn_rate;
+        self.hit_budget = other.hit_budget;
+    }
+
     pub fn spawn(&self, id: Entity, commands: &mut Commands) {
+        self.spawn_volley(id, 0, commands);
+    }
+
+    fn spawn_volley(&self, id: Entity, volley: u32, commands: &mut Commands) {
         commands.spawn(Timer::new(
             self.spawn_period,
             BulletEvent {
-                spawner: Arc::clone(&self.spawner),
+                pattern: Arc::clone(&self.pattern),
                 trigger: id,
+                base: 0.,
+                volley,
                 retrigger: true,
             },
         ));
@@ -107,51 +220,223 @@ pub fn initial_emit_bullet(
     }
 }
 
+/// Interprets the [BulletPattern] of every spawner that triggered.
 pub fn bullet_timer(
     spawners: Query<(Entity, BulletSpawner, Transform)>,
+    player: Query<Transform, With<Player>>,
     reader: EventReader<BulletEvent>,
     mut commands: Commands,
     server: Res<AssetServer>,
     mut audio_master: ResMut<AudioMaster>,
+    mut pool: ResMut<EntityPool>,
+    polygons: Res<RegularPolygons>,
 ) {
+    let target = player.iter().next().map(|t| t.translation);
+
     for BulletEvent {
         trigger,
-        spawner,
+        pattern,
+        base,
+        volley,
         retrigger,
     } in reader.read()
     {
-        let Some((entity, trigger, transform)) = spawners.get(trigger) else {
+        let Some((entity, spawner, transform)) = spawners.get(trigger) else {
             continue;
         };
 
-        spawner(transform, &mut commands, &server, &mut audio_master);
+        let context = PatternContext {
+            origin: transform.translation,
+            target,
+            base,
+            volley,
+        };
+
+        let mut fired = false;
+        pattern.fire(context, &mut |emission| match emission {
+            Emission::Shot { direction, speed } => {
+                fired = true;
+                let velocity =
+                    Vec3f::new(direction.cos(), direction.sin(), 0.) * speed * spawner.curve.at(0.);
+                match spawner.projectile {
+                    Projectile::Neutron => {
+                        let neutron = NeutronBundle::spawn(
+                            &server,
+                            *transform,
+                            Velocity(velocity),
+                            None,
+                            spawner.hits_player,
+                            &mut commands,
+                            &mut pool,
+                        );
+                        commands
+                            .get_entity(neutron)
+                            .insert(spawner.curve)
+                            .insert(spawner.hit_budget);
+                        if spawner.hits_player {
+                            commands.get_entity(neutron).insert(EnemyProjectile);
+                        }
+                        spawner
+                            .homing
+                            .attach(neutron, spawner.turn_rate, &mut commands);
+                    }
+                    Projectile::Debris => {
+                        let debris = commands
+                            .spawn((
+                                DebrisBundle::new(
+                                    transform.translation,
+                                    velocity,
+                                    direction,
+                                    &polygons,
+                                ),
+                                spawner.curve,
+                            ))
+                            .entity();
+                        if spawner.homing.is_homing() {
+                            spawner
+                                .homing
+                                .attach(debris, spawner.turn_rate, &mut commands);
+                        }
+                        if spawner.hits_player {
+                            commands.get_entity(debris).insert(EnemyProjectile);
+                        }
+                    }
+                }
+            }
+            Emission::Later {
+                delay,
+                base,
+                pattern,
+            } => {
+                commands.spawn(Timer::new(
+                    delay,
+                    BulletEvent {
+                        trigger: entity,
+                        pattern,
+                        base,
+                        volley,
+                        retrigger: false,
+                    },
+                ));
+            }
+            Emission::Custom(custom) => custom(
+                transform,
+                &mut commands,
+                &server,
+                &mut audio_master,
+                &mut pool,
+            ),
+        });
+
+        if fired {
+            NeutronBundle::spawn_audio_bundle(&mut audio_master);
+        }
         if retrigger {
-            trigger.spawn(entity, &mut commands);
+            spawner.spawn_volley(entity, volley.wrapping_add(1), &mut commands);
         }
     }
 }
 
+/// Removes bullets when they hit an enemy, unless they can pierce it.
 pub fn bullet_remover(
-    bullets: Query<RemoveOnCollision>,
+    bullets: Query<
+        (
+            Transform,
+            Option<Velocity>,
+            Option<CollisionDamage>,
+            Option<HitBudget>,
+        ),
+        With<RemoveOnCollision>,
+    >,
+    enemies: Query<Transform, (With<Enemy>, Without<Pooled>)>,
     events: EventReader<EnemyCollideEvent>,
     mut commands: Commands,
 ) {
+    let mut piercing = FxHashMap::default();
+
     for event in events.peak_read() {
-        if bullets.get(event.with).is_some() {
-            commands.get_entity(event.with).despawn();
+        let Some((transform, velocity, damage, hit_budget)) = bullets.get(event.with) else {
+            continue;
+        };
+
+        if let (Some(hit_budget), Some(obstacle)) = (hit_budget, enemies.get(event.enemy)) {
+            let (budget, velocity, damage) = piercing.entry(event.with).or_insert((
+                *hit_budget,
+                velocity.map_or(Vec3f::zero(), |v| v.0),
+                damage.map_or(0., |d| d.0),
+            ));
+            if budget.spend(
+                event.with,
+                transform.translation,
+                velocity,
+                damage,
+                obstacle.translation,
+                &mut commands,
+            ) {
+                continue;
+            }
         }
+
+        commands.get_entity(event.with).despawn();
     }
 }
 
 pub fn bullet_lifetime(
-    mut bullets: Query<(Entity, Mut<Uptime>, Lifespan)>,
+    mut bullets: Query<
+        (
+            Entity,
+            Mut<Uptime>,
+            Lifespan,
+            Transform,
+            Option<Expiry>,
+            Option<Progenitor>,
+            Option<SourceNeutron>,
+            Option<Neutron>,
+            Option<CollideWithPlayer>,
+        ),
+        Without<Pooled>,
+    >,
     mut commands: Commands,
     delta: Res<DeltaTime>,
+    mut criticality: ResMut<Criticality>,
+    mut pool: ResMut<EntityPool>,
+    mut expired: EventWriter<Expired>,
 ) {
-    for (entity, uptime, lifespan) in bullets.iter_mut() {
+    for (entity, uptime, lifespan, transform, expiry, progenitor, source, neutron, hits_player) in
+        bullets.iter_mut()
+    {
         uptime.0 += delta.delta;
         if uptime.0 >= lifespan.0 {
-            commands.get_entity(entity).despawn();
+            if let Some(spawn) = expiry.and_then(|e| e.spawn()) {
+                expired.send(Expired {
+                    position: transform.translation,
+                    spawn,
+                    hits_player: hits_player.is_some(),
+                });
+            }
+            if neutron.is_some() {
+                pool.release_neutron(entity, hits_player.is_some(), &mut commands);
+            } else {
+                commands.get_entity(entity).despawn();
+            }
+            // fission neutrons that escape the reactor
+            if progenitor.is_some_and(|p| p.0.is_some()) && source.is_none() {
+                criticality.record_losses(1);
+            }
         }
     }
 }
+
+pub fn apply_speed_curves(
+    mut bullets: Query<(Mut<Velocity>, Uptime, SpeedCurve), Without<Pooled>>,
+    delta: Res<DeltaTime>,
+) {
+    for (velocity, uptime, curve) in bullets.iter_mut() {
+        if curve.is_constant() {
+            continue;
+        }
+
+        let last = curve.at((uptime.0 - delta.delta).max(0.));
+        velocity.0 = velocity.0 * (curve.at(uptime.0) / last);
+    }
+}
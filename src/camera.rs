@@ -1,6 +1,7 @@
 use std::f32::consts::TAU;
 
 use crate::{
+    atoms::FissionEvent,
     loader::LoaderApp,
     mouse::MousePosition,
     player::{DirectionalVelocity, Player},
@@ -219,3 +220,17 @@ impl PlayerCamera {
         self.follow_point + self.shake_offset
     }
 }
+
+pub(crate) fn shake_on_fission(
+    reader: EventReader<FissionEvent>,
+    mut camera: ResMut<PlayerCamera>,
+    delta: Res<DeltaTime>,
+) {
+    if reader.peak().is_some() {
+        camera.push_screen_shake(ScreenShake::new(
+            10.,
+            0.15,
+            delta.wrapping_elapsed_as_seconds(),
+        ));
+    }
+}
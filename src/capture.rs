@@ -0,0 +1,180 @@
+use crate::{
+    atoms::{Atom, Events, ForcedFission},
+    pool::Pooled,
+    should_run_game,
+};
+use rand::{Rng, SeedableRng};
+use std::f32::consts::TAU;
+use vector::{Vec2f, Vec3f};
+use winny::{ecs::sets::IntoSystemStorage, prelude::*};
+
+#[derive(Debug)]
+pub struct CapturePlugin;
+
+impl Plugin for CapturePlugin {
+    fn build(&mut self, app: &mut App) {
+        app.insert_resource(CaptureConfig::default())
+            .egui_resource::<CaptureConfig>()
+            .add_systems(Schedule::Update, update_isotopes.run_if(should_run_game));
+    }
+}
+
+/// Neutron capture without fission.
+#[derive(Debug, Clone, Resource, AsEgui)]
+pub struct CaptureConfig {
+    /// The chance a neutron is captured instead of splitting the atom, indexed by generation.
+    pub probability: Vec<f32>,
+    /// Relative weights of the heavy, unstable and inert outcomes of a capture.
+    pub heavy: f32,
+    pub unstable: f32,
+    pub inert: f32,
+    /// How long an unstable atom lasts before it splits on its own, in seconds.
+    pub min_decay: f32,
+    pub max_decay: f32,
+}
+
+impl Default for CaptureConfig {
+    fn default() -> Self {
+        Self {
+            probability: vec![0.1, 0.12, 0.15, 0.18, 0.2, 0.2, 0.],
+            heavy: 0.5,
+            unstable: 0.3,
+            inert: 0.2,
+            min_decay: 1.5,
+            max_decay: 4.0,
+        }
+    }
+}
+
+impl CaptureConfig {
+    fn roll_outcome(&self, rng: &mut impl Rng) -> IsotopeState {
+        let [heavy, unstable, inert] = [self.heavy, self.unstable, self.inert].map(|w| w.max(0.));
+        let total = heavy + unstable + inert;
+        if total <= 0. {
+            return IsotopeState::Heavy;
+        }
+
+        let roll = rng.gen_range(0f32..total);
+        if roll < heavy {
+            IsotopeState::Heavy
+        } else if roll < heavy + unstable {
+            let decay = if self.max_decay > self.min_decay {
+                rng.gen_range(self.min_decay..self.max_decay)
+            } else {
+                self.min_decay
+            };
+            IsotopeState::Unstable { decay }
+        } else {
+            IsotopeState::Inert
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum IsotopeState {
+    /// Not yet given a capture chance from the [CaptureConfig].
+    #[default]
+    Fresh,
+    Fissile,
+    /// Captured a neutron this frame, resolved into one of the states below.
+    Captured,
+    /// Releases an extra neutron when it splits.
+    Heavy,
+    /// Splits on its own once the decay timer runs out.
+    Unstable {
+        decay: f32,
+    },
+    /// Absorbs neutrons and never splits.
+    Inert,
+}
+
+/// What happens to an atom when a neutron hits it.
+///
+/// Every atom carries one so pooled atoms can be reused without changing their archetype.
+#[derive(Debug, Default, Clone, Copy, Component)]
+pub struct Isotope {
+    capture_chance: f32,
+    state: IsotopeState,
+}
+
+impl Isotope {
+    pub fn state(&self) -> IsotopeState {
+        self.state
+    }
+
+    pub fn is_inert(&self) -> bool {
+        self.state == IsotopeState::Inert
+    }
+
+    /// Rolls whether a neutron hitting this atom is captured.
+    ///
+    /// Only fissile atoms capture, atoms that already captured a neutron always split.
+    pub fn captures(&self, rng: &mut impl Rng) -> bool {
+        self.state == IsotopeState::Fissile
+            && self.capture_chance > 0.
+            && rng.gen_bool(self.capture_chance.min(1.) as f64)
+    }
+
+    pub fn captured(&self) -> Self {
+        Self {
+            state: IsotopeState::Captured,
+            ..*self
+        }
+    }
+
+    /// Extra neutrons released when this atom splits.
+    pub fn bonus_neutrons(&self) -> usize {
+        match self.state {
+            IsotopeState::Heavy => 1,
+            _ => 0,
+        }
+    }
+}
+
+fn update_isotopes(
+    mut isotopes: Query<
+        (Entity, Mut<Transform>, Events, Mut<Isotope>),
+        (With<Atom>, Without<Pooled>),
+    >,
+    config: Res<CaptureConfig>,
+    mut fission: EventWriter<ForcedFission>,
+    delta: Res<DeltaTime>,
+) {
+    let mut rng = rand::rngs::SmallRng::from_entropy();
+
+    for (atom, transform, events, isotope) in isotopes.iter_mut() {
+        match isotope.state {
+            IsotopeState::Fresh => {
+                isotope.capture_chance = config
+                    .probability
+                    .get(events.0 as usize)
+                    .copied()
+                    .unwrap_or_default();
+                isotope.state = IsotopeState::Fissile;
+            }
+            IsotopeState::Captured => {
+                isotope.state = config.roll_outcome(&mut rng);
+                // fragments spawn at half scale
+                let scale = match isotope.state {
+                    IsotopeState::Heavy => 0.6,
+                    IsotopeState::Inert => 0.35,
+                    _ => 0.5,
+                };
+                transform.scale = Vec2f::new(scale, scale);
+            }
+            IsotopeState::Unstable { ref mut decay } => {
+                *decay -= delta.delta;
+                if *decay <= 0. {
+                    isotope.state = IsotopeState::Fissile;
+                    isotope.capture_chance = 0.;
+                    let angle = rng.gen_range(0f32..TAU);
+                    fission.send(ForcedFission {
+                        atom,
+                        direction: Vec3f::new(angle.cos(), angle.sin(), 0.),
+                    });
+                }
+            }
+            IsotopeState::Fissile | IsotopeState::Heavy | IsotopeState::Inert => {}
+        }
+    }
+}
@@ -12,7 +12,7 @@ use winny::{
 use crate::should_run_game;
 
 pub mod indicators;
-mod spatial;
+pub(crate) mod spatial;
 mod systems;
 
 #[derive(Debug)]
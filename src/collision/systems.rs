@@ -1,9 +1,9 @@
 use super::*;
-use crate::{player::Player, Enemy};
+use crate::{player::Player, pool::Pooled, Enemy};
 use spatial::{SpatialData, SpatialHash};
 
 pub fn update_player_collision(
-    colliders: Query<(Entity, Transform, Collider), With<CollideWithPlayer>>,
+    colliders: Query<(Entity, Transform, Collider), (With<CollideWithPlayer>, Without<Pooled>)>,
     player: Query<(Transform, Collider), With<Player>>,
     mut map: ResMut<PlayerCollisionMap>,
     mut writer: EventWriter<PlayerCollideEvent>,
@@ -13,6 +13,8 @@ pub fn update_player_collision(
     };
     let p_coll = p_coll.absolute(p_trans);
     let set = &mut map.0;
+    // forget entities that were despawned or pooled so they collide again once reused
+    set.retain(|e| colliders.get(*e).is_some());
 
     let mut spatial = SpatialHash::new(100.);
 
@@ -59,11 +61,13 @@ pub fn update_player_collision(
 }
 
 pub fn update_enemy_collision(
-    colliders: Query<(Entity, Transform, Collider), With<CollideWithEnemy>>,
-    enemies: Query<(Entity, Transform, Collider), With<Enemy>>,
+    colliders: Query<(Entity, Transform, Collider), (With<CollideWithEnemy>, Without<Pooled>)>,
+    enemies: Query<(Entity, Transform, Collider), (With<Enemy>, Without<Pooled>)>,
     mut map: ResMut<EnemyCollisionMap>,
     mut writer: EventWriter<EnemyCollideEvent>,
 ) {
+    map.0.retain(|e, _| enemies.get(*e).is_some());
+
     // the grid size is very small because there's not much penalty for sparse grid distribution
     let mut spatial = SpatialHash::new(100.);
 
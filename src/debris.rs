@@ -0,0 +1,133 @@
+use crate::{
+    atoms::FissionEvent,
+    bullet::spawner::{Lifespan, Uptime},
+    collision::{CircleCollider, CollideWithPlayer, Collider},
+    regular::RegularPolygons,
+    shaders::{materials::DebrisMaterial, SpaceHaze},
+    should_run_game, CollisionDamage, Velocity,
+};
+use mesh2d::Mesh2d;
+use rand::{Rng, SeedableRng};
+use std::f32::consts::TAU;
+use vector::{Vec2f, Vec3f};
+use winny::{
+    ecs::sets::IntoSystemStorage,
+    gfx::cgmath::{Quaternion, Rad, Rotation3},
+    prelude::*,
+};
+
+#[derive(Debug)]
+pub struct DebrisPlugin;
+
+impl Plugin for DebrisPlugin {
+    fn build(&mut self, app: &mut App) {
+        app.add_systems(Schedule::Update, decay_debris.run_if(should_run_game));
+    }
+}
+
+/// Shards left behind by each terminal fission.
+const DEBRIS_COUNT: usize = 3;
+const DEBRIS_LIFESPAN: f32 = 6.;
+const DEBRIS_DAMAGE: f32 = 1.;
+const DEBRIS_SPEED: f32 = 0.6;
+/// How much velocity debris keeps each second.
+const DEBRIS_DRAG: f32 = 0.4;
+
+/// Radioactive debris left behind by a generation 6 fission.
+///
+/// Hurts the player on contact and decays over its [Lifespan], fading out and
+/// doing less damage as it does. Despawned by [bullet_lifetime](crate::bullet::spawner::bullet_lifetime).
+#[derive(Debug, Clone, Copy, Component)]
+pub struct Debris;
+
+#[derive(Bundle)]
+pub struct DebrisBundle {
+    debris: Debris,
+    transform: Transform,
+    velocity: Velocity,
+    collider: Collider,
+    with_player: CollideWithPlayer,
+    damage: CollisionDamage,
+    lifespan: Lifespan,
+    uptime: Uptime,
+    mesh: Handle<Mesh2d>,
+    material: DebrisMaterial,
+}
+
+impl DebrisBundle {
+    pub fn new(
+        position: Vec3f,
+        velocity: Vec3f,
+        rotation: f32,
+        polygons: &RegularPolygons,
+    ) -> Self {
+        Self {
+            debris: Debris,
+            transform: Transform {
+                translation: position,
+                rotation: Quaternion::from_angle_z(Rad(rotation)),
+                scale: Vec2f::new(0.3, 0.3),
+            },
+            velocity: Velocity(velocity),
+            collider: Collider::Circle(CircleCollider {
+                position: Vec3f::zero(),
+                radius: 12.,
+            }),
+            with_player: CollideWithPlayer,
+            damage: CollisionDamage(DEBRIS_DAMAGE),
+            lifespan: Lifespan(DEBRIS_LIFESPAN),
+            uptime: Uptime(0.),
+            mesh: polygons.0[0].clone(),
+            material: DebrisMaterial {
+                modulation: Modulation(SpaceHaze::purple()),
+            },
+        }
+    }
+
+    /// Scatters debris around the position of a terminal fission.
+    pub fn scatter(position: Vec3f, commands: &mut Commands, polygons: &RegularPolygons) {
+        let mut rng = rand::rngs::SmallRng::from_entropy();
+        for _ in 0..DEBRIS_COUNT {
+            let angle = rng.gen_range(0f32..TAU);
+            let speed = rng.gen_range(0.5f32..1.) * DEBRIS_SPEED;
+            commands.spawn(Self::new(
+                position,
+                Vec3f::new(angle.cos(), angle.sin(), 0.) * speed,
+                rng.gen_range(0f32..TAU),
+                polygons,
+            ));
+        }
+    }
+}
+
+pub(crate) fn scatter_debris(
+    reader: EventReader<FissionEvent>,
+    mut commands: Commands,
+    polygons: Res<RegularPolygons>,
+) {
+    for event in reader.peak_read().filter(|e| e.generation >= 6) {
+        DebrisBundle::scatter(event.position, &mut commands, &polygons);
+    }
+}
+
+fn decay_debris(
+    mut debris: Query<
+        (
+            Mut<DebrisMaterial>,
+            Mut<CollisionDamage>,
+            Mut<Velocity>,
+            Uptime,
+            Lifespan,
+        ),
+        With<Debris>,
+    >,
+    delta: Res<DeltaTime>,
+) {
+    let drag = DEBRIS_DRAG.powf(delta.delta);
+    for (material, damage, velocity, uptime, lifespan) in debris.iter_mut() {
+        let remaining = (1. - uptime.0 / lifespan.0.max(f32::EPSILON)).clamp(0., 1.);
+        material.modulation.0.w = remaining;
+        damage.0 = DEBRIS_DAMAGE * remaining;
+        velocity.0 = velocity.0 * drag;
+    }
+}
@@ -0,0 +1,166 @@
+use crate::{
+    atoms::{Atom, Events},
+    bullet::{NeutronBundle, Progenitor},
+    lineage::Lineage,
+    pool::{EntityPool, Pooled},
+    reactor::Criticality,
+    should_run_game, Velocity,
+};
+use rand::{Rng, SeedableRng};
+use server::AssetServer;
+use std::f32::consts::TAU;
+use vector::{Vec2f, Vec3f};
+use winny::{ecs::sets::IntoSystemStorage, prelude::*};
+
+#[derive(Debug)]
+pub struct DelayedNeutronPlugin;
+
+impl Plugin for DelayedNeutronPlugin {
+    fn build(&mut self, app: &mut App) {
+        app.insert_resource(DelayedNeutronConfig::default())
+            .egui_resource::<DelayedNeutronConfig>()
+            .add_systems(
+                Schedule::Update,
+                emit_delayed_neutrons.run_if(should_run_game),
+            );
+    }
+}
+
+/// How fragments of a single generation emit delayed neutrons.
+#[derive(Debug, Clone, Copy, PartialEq, AsEgui)]
+pub struct DelayedNeutrons {
+    /// The chance that a fragment emits at all.
+    pub probability: f32,
+    pub count: u32,
+    /// Delay range in seconds.
+    pub min_delay: f32,
+    pub max_delay: f32,
+}
+
+impl DelayedNeutrons {
+    pub const NONE: Self = Self {
+        probability: 0.,
+        count: 0,
+        min_delay: 0.,
+        max_delay: 0.,
+    };
+}
+
+/// Delayed neutron emission indexed by the generation of the fragment.
+#[derive(Debug, Clone, Resource, AsEgui)]
+pub struct DelayedNeutronConfig {
+    pub generations: Vec<DelayedNeutrons>,
+}
+
+impl Default for DelayedNeutronConfig {
+    fn default() -> Self {
+        let emit = |probability: f32, count: u32, min_delay: f32, max_delay: f32| DelayedNeutrons {
+            probability,
+            count,
+            min_delay,
+            max_delay,
+        };
+
+        Self {
+            generations: vec![
+                DelayedNeutrons::NONE,
+                emit(0.4, 2, 1.0, 3.0),
+                emit(0.35, 1, 1.0, 3.5),
+                emit(0.3, 1, 1.5, 4.0),
+                emit(0.25, 1, 1.5, 4.5),
+                emit(0.2, 1, 2.0, 5.0),
+                DelayedNeutrons::NONE,
+            ],
+        }
+    }
+}
+
+impl DelayedNeutronConfig {
+    /// Rolls the delay of each neutron a fragment of the given generation will emit.
+    pub fn roll(&self, generation: u32, rng: &mut impl Rng) -> Vec<f32> {
+        let Some(config) = self.generations.get(generation as usize) else {
+            return Vec::new();
+        };
+
+        if config.count == 0 || !rng.gen_bool(config.probability.clamp(0., 1.) as f64) {
+            return Vec::new();
+        }
+
+        (0..config.count)
+            .map(|_| {
+                if config.max_delay > config.min_delay {
+                    rng.gen_range(config.min_delay..config.max_delay)
+                } else {
+                    config.min_delay
+                }
+            })
+            .collect()
+    }
+}
+
+/// Releases neutrons from a fission product after a delay.
+///
+/// Every atom carries one so pooled atoms can be reused without changing their archetype.
+/// Emitters start unarmed and are rolled the first frame their atom is live, only fission
+/// fragments ever get any delays.
+#[derive(Debug, Default, Clone, Component)]
+pub struct DelayedEmitter {
+    armed: bool,
+    delays: Vec<f32>,
+}
+
+fn emit_delayed_neutrons(
+    mut emitters: Query<
+        (Transform, Progenitor, Lineage, Events, Mut<DelayedEmitter>),
+        (With<Atom>, Without<Pooled>),
+    >,
+    config: Res<DelayedNeutronConfig>,
+    mut commands: Commands,
+    mut pool: ResMut<EntityPool>,
+    mut criticality: ResMut<Criticality>,
+    server: Res<AssetServer>,
+    delta: Res<DeltaTime>,
+) {
+    let mut rng = rand::rngs::SmallRng::from_entropy();
+
+    for (transform, progenitor, lineage, events, emitter) in emitters.iter_mut() {
+        if !emitter.armed {
+            emitter.armed = true;
+            emitter.delays = match progenitor.0 {
+                Some(_) => config.roll(events.0, &mut rng),
+                None => Vec::new(),
+            };
+        }
+
+        if emitter.delays.is_empty() {
+            continue;
+        }
+
+        for delay in emitter.delays.iter_mut() {
+            *delay -= delta.delta;
+        }
+
+        let ready = emitter.delays.iter().filter(|d| **d <= 0.).count();
+        emitter.delays.retain(|d| *d > 0.);
+
+        for _ in 0..ready {
+            let angle = rng.gen_range(0f32..TAU);
+            criticality.record_births(1);
+            // shares the fragment's progenitor and lineage so it can't split the fragment that released it
+            let neutron = NeutronBundle::spawn(
+                &server,
+                Transform {
+                    translation: transform.translation,
+                    scale: Vec2f::one(),
+                    ..Default::default()
+                },
+                Velocity(Vec3f::new(angle.cos(), angle.sin(), 0.) * 2.),
+                progenitor.0,
+                true,
+                &mut commands,
+                &mut pool,
+            );
+            commands.get_entity(neutron).insert(lineage.reborn());
+        }
+    }
+}
@@ -11,12 +11,13 @@ use winny::prelude::*;
 use crate::{
     atoms::AtomBundle,
     audio::AudioMaster,
-    bullet::{NeutronBundle, RadialVelocity},
+    bullet::{homing::Homing, EnemyProjectile, NeutronBundle, RadialVelocity},
     collision::{CircleCollider, CollideWithPlayer, Collider, EnemyCollideEvent},
     player::Player,
+    pool::EntityPool,
     regular::RegularPolygons,
     shaders::{materials::HeptaMaterial, Crimson},
-    should_run_game, ChildOffset, CollisionDamage, Enemy, Parent, Velocity,
+    should_run_game, ChildOffset, CollisionDamage, Enemy, Health, Parent, Velocity,
 };
 
 #[derive(Debug)]
@@ -24,13 +25,22 @@ pub struct EnemyPlugin;
 
 impl Plugin for EnemyPlugin {
     fn build(&mut self, app: &mut App) {
-        app.insert_resource(EnemySpawner::new()).add_systems(
-            Schedule::Update,
-            (update_heading_towards_player, update_regular, spawn_enemies).run_if(should_run_game),
-        );
+        app.insert_resource(EnemySpawner::new())
+            .register_event::<EnemyKilled>()
+            .add_systems(
+                Schedule::Update,
+                (update_heading_towards_player, update_regular, spawn_enemies)
+                    .run_if(should_run_game),
+            );
     }
 }
 
+/// Sent by [update_regular] for every enemy that dies.
+#[derive(Debug, Clone, Copy, Event)]
+pub struct EnemyKilled {
+    pub position: Vec3f,
+}
+
 /// Facilitates "steering" behavior, giving enemies a feeling of momentum.
 #[derive(Debug, Default, PartialEq, Clone, Copy, Component)]
 pub struct Heading {
@@ -82,7 +92,7 @@ impl Heading {
 }
 
 pub fn update_heading_towards_player(
-    mut q: Query<(Mut<Velocity>, Mut<Heading>, Transform, TurnSpeed)>,
+    mut q: Query<(Mut<Velocity>, Mut<Heading>, Transform, TurnSpeed), Without<Homing>>,
     player: Query<Transform, With<Player>>,
     time: Res<DeltaTime>,
 ) {
@@ -115,6 +125,7 @@ pub fn spawn_regular(
     commands: &mut Commands,
     server: &AssetServer,
     audio: &mut AudioMaster,
+    pool: &mut EntityPool,
     children: usize,
 ) {
     let mut enemy_cloud = Vec::new();
@@ -133,6 +144,7 @@ pub fn spawn_regular(
             Heading::new(rng.gen_range(1.5f32..4f32)),
             TurnSpeed(rng.gen_range(1f32..3f32)),
             CollisionDamage(1.),
+            Health::new(3., 0.),
             SpinSpeed(rng.gen_range(-2f32..2f32)),
             RadialVelocity::new(Radf(PI)),
             (
@@ -161,6 +173,7 @@ pub fn spawn_regular(
             0,
             server,
             audio,
+            pool,
         );
         // push_child(parent, entity, commands, parents);
         commands
@@ -180,27 +193,40 @@ pub fn spawn_regular(
 }
 
 fn update_regular(
-    mut q: Query<(Entity, SpinSpeed, Transform, EnemyCloud, Mut<RegularEnemy>)>,
+    mut q: Query<(
+        Entity,
+        SpinSpeed,
+        Transform,
+        EnemyCloud,
+        Mut<RegularEnemy>,
+        Health,
+    )>,
     children: Query<Mut<ChildOffset>>,
     player_bullet: Query<Entity, Without<CollideWithPlayer>>,
     parent_haver: Query<Parent>,
     velocity_haver: Query<Velocity>,
     time: Res<DeltaTime>,
     collision: EventReader<EnemyCollideEvent>,
+    mut killed: EventWriter<EnemyKilled>,
     server: Res<AssetServer>,
     mut commands: Commands,
+    mut pool: ResMut<EntityPool>,
 ) {
-    for (entity, spin, transform, cloud, angle) in q.iter_mut() {
+    for (entity, spin, transform, cloud, angle, health) in q.iter_mut() {
         angle.0 += time.delta * spin.0;
 
-        if let Some(event) = collision.peak_read().find(|e| e.enemy == entity) {
+        // killed either by a direct hit or worn down by shockwaves
+        if health.is_depleted() || collision.peak_read().any(|e| e.enemy == entity) {
             commands.get_entity(entity).despawn();
+            killed.send(EnemyKilled {
+                position: transform.translation,
+            });
             // let mut rng = rand::rngs::SmallRng::from_entropy();
 
             for i in 0..cloud.0.len() {
                 let direction = ((i as f32 / cloud.0.len() as f32) + angle.0) * TAU;
 
-                NeutronBundle::spawn(
+                let neutron = NeutronBundle::spawn(
                     &server,
                     Transform {
                         translation: transform.translation,
@@ -211,7 +237,9 @@ fn update_regular(
                     None,
                     true,
                     &mut commands,
+                    &mut pool,
                 );
+                commands.get_entity(neutron).insert(EnemyProjectile);
             }
 
             // for child in cloud.0.iter() {
@@ -314,6 +342,7 @@ pub fn spawn_enemies(
     mut commands: Commands,
     polygons: Res<RegularPolygons>,
     mut audio: ResMut<AudioMaster>,
+    mut pool: ResMut<EntityPool>,
 ) {
     spawner.time_elapsed += time.delta;
     let mut rng = rand::thread_rng();
@@ -340,6 +369,7 @@ pub fn spawn_enemies(
             &mut commands,
             &server,
             &mut audio,
+            &mut pool,
             rng.gen_range(3..7),
         )
     }
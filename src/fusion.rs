@@ -0,0 +1,196 @@
+use crate::{
+    atoms::{Atom, AtomBundle, Events},
+    audio::AudioMaster,
+    bullet::Progenitor,
+    camera::{PlayerCamera, ScreenShake},
+    collision::{
+        spatial::{SpatialData, SpatialHash},
+        Collider, CollidesWith,
+    },
+    lineage::{Lineage, LineageConfig},
+    pool::{EntityPool, Pooled},
+    regular::RegularPolygons,
+    should_run_game, Parent, Velocity,
+};
+use fxhash::FxHashSet;
+use server::AssetServer;
+use vector::Vec3f;
+use winny::{ecs::sets::IntoSystemStorage, prelude::*};
+
+#[derive(Debug)]
+pub struct FusionPlugin;
+
+impl Plugin for FusionPlugin {
+    fn build(&mut self, app: &mut App) {
+        app.insert_resource(FusionConfig::default())
+            .egui_resource::<FusionConfig>()
+            .register_event::<FusionEvent>()
+            .add_systems(Schedule::PostUpdate, fuse_fragments.run_if(should_run_game));
+    }
+}
+
+/// Tuning for fragment fusion.
+#[derive(Debug, Resource, AsEgui)]
+pub struct FusionConfig {
+    /// Fragments must be moving slower than this relative to each other to fuse.
+    max_relative_speed: f32,
+    /// Each fragment must also be moving slower than this, fission throws fragments at a speed
+    /// of 1 so only fragments that were pushed around are too fast.
+    max_speed: f32,
+    /// The largest difference in generation that can still fuse.
+    max_generation_gap: u32,
+}
+
+impl Default for FusionConfig {
+    fn default() -> Self {
+        Self {
+            max_relative_speed: 1.0,
+            max_speed: 1.2,
+            max_generation_gap: 1,
+        }
+    }
+}
+
+/// Sent when two fragments fuse into a new atom.
+#[derive(Debug, Clone, Copy, Event)]
+pub struct FusionEvent {
+    pub position: Vec3f,
+    /// The generation of the fused atom.
+    pub generation: u32,
+}
+
+fn fusion_audio(server: &AssetServer) -> AudioBundle {
+    AudioBundle {
+        handle: server.load("res/RPG_Essentials_Free/8_Buffs_Heals_SFX/30_Revive_03.wav"),
+        playback_settings: PlaybackSettings::default().with_volume(8.0),
+    }
+}
+
+/// Fuses two slow fragments of compatible generation that touch into one atom of a lower generation.
+fn fuse_fragments(
+    fragments: Query<
+        (
+            Entity,
+            Transform,
+            Collider,
+            Velocity,
+            Events,
+            Progenitor,
+            Lineage,
+        ),
+        (With<Atom>, Without<Pooled>, Without<Parent>),
+    >,
+    config: Res<FusionConfig>,
+    lineage_config: Res<LineageConfig>,
+    mut writer: EventWriter<FusionEvent>,
+    mut commands: Commands,
+    mut pool: ResMut<EntityPool>,
+    mut camera: ResMut<PlayerCamera>,
+    polygons: Res<RegularPolygons>,
+    server: Res<AssetServer>,
+    mut audio: ResMut<AudioMaster>,
+    delta: Res<DeltaTime>,
+) {
+    let mut spatial = SpatialHash::new(100.);
+    for (entity, transform, collider, _, events, _, _) in fragments.iter() {
+        // only fission products fuse
+        if events.0 == 0 {
+            continue;
+        }
+
+        let absolute = collider.absolute(transform);
+        spatial.insert(SpatialData {
+            entity,
+            position: absolute.position(),
+            collider: absolute,
+        });
+    }
+
+    let mut fused = FxHashSet::default();
+    for (entity, transform, collider, velocity, events, progenitor, lineage) in fragments.iter() {
+        if events.0 == 0 || fused.contains(&entity) || velocity.0.magnitude() > config.max_speed {
+            continue;
+        }
+
+        let absolute = collider.absolute(transform);
+        let partner = spatial
+            .nearby_objects(&absolute.position())
+            .filter(|other| other.entity != entity && !fused.contains(&other.entity))
+            .filter(|other| absolute.collides_with(&other.collider))
+            .find_map(|other| {
+                let (
+                    _,
+                    other_transform,
+                    _,
+                    other_velocity,
+                    other_events,
+                    other_progenitor,
+                    other_lineage,
+                ) = fragments.get(other.entity)?;
+
+                // siblings overlap the moment they are spawned
+                if progenitor.0.is_some() && progenitor == other_progenitor {
+                    return None;
+                }
+
+                if lineage_config.is_immune(lineage, other_lineage) {
+                    return None;
+                }
+
+                if events.0.abs_diff(other_events.0) > config.max_generation_gap {
+                    return None;
+                }
+
+                let relative = velocity.0 - other_velocity.0;
+                if other_velocity.0.magnitude() > config.max_speed
+                    || relative.magnitude() > config.max_relative_speed
+                {
+                    return None;
+                }
+
+                Some((
+                    other.entity,
+                    *other_transform,
+                    *other_velocity,
+                    *other_events,
+                ))
+            });
+
+        let Some((other, other_transform, other_velocity, other_events)) = partner else {
+            continue;
+        };
+
+        fused.insert(entity);
+        fused.insert(other);
+        pool.release_atom(entity, events.0, &mut commands);
+        pool.release_atom(other, other_events.0, &mut commands);
+
+        let position = (transform.translation + other_transform.translation) * 0.5;
+        let generation = events.0.min(other_events.0).saturating_sub(1);
+        AtomBundle::spawn(
+            &mut commands,
+            position,
+            Some((velocity.0 + other_velocity.0) * 0.5),
+            None,
+            &polygons,
+            generation,
+            &server,
+            &mut audio,
+            &mut pool,
+        );
+
+        writer.send(FusionEvent {
+            position,
+            generation,
+        });
+    }
+
+    if !fused.is_empty() {
+        commands.spawn(fusion_audio(&server));
+        camera.push_screen_shake(ScreenShake::new(
+            4.,
+            0.3,
+            delta.wrapping_elapsed_as_seconds(),
+        ));
+    }
+}
@@ -0,0 +1,299 @@
+use crate::{
+    bullet::Neutron,
+    collision::{
+        spatial::{SpatialData, SpatialHash},
+        CircleCollider, Collider, CollidesWith,
+    },
+    pool::Pooled,
+    regular::RegularPolygons,
+    rods::{ControlRodLayout, RodPlacement},
+    shaders::{materials::HexaMaterial, SpaceHaze},
+    should_run_game, Velocity,
+};
+use fxhash::{FxHashMap, FxHashSet};
+use mesh2d::Mesh2d;
+use noise::NoiseFn;
+use rand::{seq::SliceRandom, Rng, SeedableRng};
+use vector::{Vec2f, Vec3f};
+use winny::{ecs::sets::IntoSystemStorage, prelude::*};
+
+#[derive(Debug)]
+pub struct CoreLayoutPlugin;
+
+impl Plugin for CoreLayoutPlugin {
+    fn build(&mut self, app: &mut App) {
+        app.insert_resource(CoreLayoutConfig::default())
+            .insert_resource(ModeratedNeutrons::default())
+            .egui_resource::<CoreLayoutConfig>()
+            .add_systems(
+                Schedule::PostUpdate,
+                moderate_neutrons.run_if(should_run_game),
+            );
+    }
+}
+
+/// Parameters for the reactor core generated at the start of each run.
+#[derive(Debug, Resource, AsEgui)]
+pub struct CoreLayoutConfig {
+    /// Falls back to an empty field with the default [ControlRodLayout] when disabled.
+    enabled: bool,
+    seed: u32,
+    /// Picks a new seed every run, the seed is logged so a layout can be reproduced.
+    random_seed: bool,
+    /// Half the width of the square the core is generated in.
+    extent: f32,
+    /// Distance between the points the noise is sampled at.
+    cell_size: f32,
+    /// Nothing is placed this close to where the player spawns.
+    clear_radius: f32,
+    /// How quickly the fuel density changes over space.
+    fuel_frequency: f32,
+    /// Noise above this is fuel, in the range [-1, 1].
+    fuel_threshold: f32,
+    max_fuel: usize,
+    /// How quickly the empty lanes wind.
+    lane_frequency: f32,
+    /// Cells where the lane noise is closer to zero than this are left empty.
+    lane_width: f32,
+    /// Noise below this may hold a moderator, in the range [-1, 1].
+    moderator_threshold: f32,
+    moderator_chance: f32,
+    /// Speed neutrons keep after passing through a moderator.
+    moderation: f32,
+    rod_count: usize,
+    /// Minimum distance between two rods.
+    rod_spacing: f32,
+}
+
+impl Default for CoreLayoutConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            seed: 1,
+            random_seed: true,
+            extent: 1600.,
+            cell_size: 110.,
+            clear_radius: 350.,
+            fuel_frequency: 0.0012,
+            fuel_threshold: 0.3,
+            max_fuel: 120,
+            lane_frequency: 0.0006,
+            lane_width: 0.08,
+            moderator_threshold: -0.45,
+            moderator_chance: 0.25,
+            moderation: 0.4,
+            rod_count: 6,
+            rod_spacing: 500.,
+        }
+    }
+}
+
+/// The placements for a generated reactor core.
+#[derive(Debug, Clone)]
+pub struct CoreLayout {
+    pub fuel: Vec<Vec3f>,
+    pub moderators: Vec<Vec3f>,
+    pub rods: ControlRodLayout,
+}
+
+impl CoreLayoutConfig {
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// The seed the next layout should be generated from.
+    pub fn next_seed(&self, rng: &mut impl Rng) -> u32 {
+        if self.random_seed {
+            rng.gen()
+        } else {
+            self.seed
+        }
+    }
+
+    /// Generates a layout around the origin.
+    ///
+    /// Fuel clusters where one noise field is high, winding empty lanes follow the zero
+    /// crossings of a second, lower frequency field. Moderators are scattered in the sparse
+    /// regions and rods guard the edges of the clusters.
+    pub fn generate(&self, seed: u32) -> CoreLayout {
+        let density = noise::OpenSimplex::new(seed);
+        let lanes = noise::OpenSimplex::new(seed.wrapping_add(1));
+        let mut rng = rand::rngs::SmallRng::seed_from_u64(seed as u64);
+
+        let cell_size = self.cell_size.max(10.);
+        let cells = (self.extent / cell_size).ceil() as i32;
+        let jitter = cell_size * 0.25;
+
+        let mut layout = CoreLayout {
+            fuel: Vec::new(),
+            moderators: Vec::new(),
+            rods: ControlRodLayout(Vec::new()),
+        };
+        let mut rod_candidates = Vec::new();
+
+        for x in -cells..=cells {
+            for y in -cells..=cells {
+                let center = Vec3f::new(x as f32 * cell_size, y as f32 * cell_size, 0.);
+                if center.magnitude() < self.clear_radius {
+                    continue;
+                }
+
+                let sample = |noise: &noise::OpenSimplex, frequency: f32| {
+                    noise.get([(center.x * frequency) as f64, (center.y * frequency) as f64]) as f32
+                };
+
+                if sample(&lanes, self.lane_frequency).abs() < self.lane_width {
+                    continue;
+                }
+
+                let position = center
+                    + Vec3f::new(
+                        rng.gen_range(-jitter..=jitter),
+                        rng.gen_range(-jitter..=jitter),
+                        0.,
+                    );
+
+                let value = sample(&density, self.fuel_frequency);
+                if value > self.fuel_threshold {
+                    layout.fuel.push(position);
+                } else if value > self.fuel_threshold - 0.15 {
+                    rod_candidates.push(center);
+                } else if value < self.moderator_threshold
+                    && rng.gen_bool(self.moderator_chance.clamp(0., 1.) as f64)
+                {
+                    layout.moderators.push(position);
+                }
+            }
+        }
+
+        if layout.fuel.len() > self.max_fuel {
+            layout.fuel.shuffle(&mut rng);
+            layout.fuel.truncate(self.max_fuel);
+        }
+
+        rod_candidates.shuffle(&mut rng);
+        for candidate in rod_candidates.into_iter() {
+            if layout.rods.0.len() >= self.rod_count {
+                break;
+            }
+
+            let spacing = self.rod_spacing.powi(2);
+            if layout
+                .rods
+                .0
+                .iter()
+                .any(|rod| rod.position.dist2(&candidate) < spacing)
+            {
+                continue;
+            }
+
+            layout.rods.0.push(RodPlacement {
+                position: candidate,
+                // leave the player some rods they can't pull
+                retractable: layout.rods.0.len() % 3 != 2,
+            });
+        }
+
+        layout
+    }
+}
+
+/// Slows down neutrons that pass through it.
+///
+/// Moderators are not [Enemy](crate::Enemy)s, they check for neutrons themselves so nothing
+/// that targets or collides with enemies picks them up.
+#[derive(Debug, Clone, Copy, Component)]
+pub struct Moderator {
+    /// Speed neutrons keep after passing through.
+    pub factor: f32,
+}
+
+const MODERATOR_RADIUS: f32 = 60.;
+
+#[derive(Bundle)]
+pub struct ModeratorBundle {
+    moderator: Moderator,
+    transform: Transform,
+    collider: Collider,
+    mesh: Handle<Mesh2d>,
+    material: HexaMaterial,
+}
+
+impl ModeratorBundle {
+    pub fn new(position: Vec3f, factor: f32, polygons: &RegularPolygons) -> Self {
+        let mut color = SpaceHaze::white();
+        color.w = 0.3;
+
+        Self {
+            moderator: Moderator { factor },
+            transform: Transform {
+                translation: position,
+                scale: Vec2f::new(1.5, 1.5),
+                ..Default::default()
+            },
+            collider: Collider::Circle(CircleCollider {
+                position: Vec3f::zero(),
+                radius: MODERATOR_RADIUS,
+            }),
+            // the polygons have a radius of 40
+            mesh: polygons.0[3].clone(),
+            material: HexaMaterial {
+                modulation: Modulation(color),
+            },
+        }
+    }
+}
+
+/// Spawns the moderators of a layout, fuel and rods are spawned by their own bundles.
+pub fn spawn_moderators(
+    commands: &mut Commands,
+    layout: &CoreLayout,
+    config: &CoreLayoutConfig,
+    polygons: &RegularPolygons,
+) {
+    for position in layout.moderators.iter() {
+        commands.spawn(ModeratorBundle::new(*position, config.moderation, polygons));
+    }
+}
+
+/// The neutrons inside each moderator, they are only slowed down when they enter.
+#[derive(Debug, Default, Resource)]
+struct ModeratedNeutrons(FxHashMap<Entity, FxHashSet<Entity>>);
+
+fn moderate_neutrons(
+    moderators: Query<(Entity, Moderator, Transform, Collider)>,
+    mut neutrons: Query<
+        (Entity, Mut<Velocity>, Transform, Collider),
+        (With<Neutron>, Without<Pooled>),
+    >,
+    mut moderated: ResMut<ModeratedNeutrons>,
+) {
+    moderated.0.retain(|e, _| moderators.get(*e).is_some());
+
+    let mut spatial = SpatialHash::new(100.);
+    for (entity, _, transform, collider) in neutrons.iter() {
+        let absolute = collider.absolute(transform);
+        spatial.insert(SpatialData {
+            entity,
+            position: absolute.position(),
+            collider: absolute,
+        });
+    }
+
+    for (entity, moderator, transform, collider) in moderators.iter() {
+        let absolute = collider.absolute(transform);
+        let inside = spatial
+            .nearby_objects(&absolute.position())
+            .filter(|other| absolute.collides_with(&other.collider))
+            .map(|other| other.entity)
+            .collect::<FxHashSet<_>>();
+
+        let previous = moderated.0.entry(entity).or_default();
+        for neutron in inside.iter().filter(|n| !previous.contains(*n)) {
+            if let Some((_, velocity, _, _)) = neutrons.get_mut(*neutron) {
+                velocity.0 = velocity.0 * moderator.factor;
+            }
+        }
+        *previous = inside;
+    }
+}
@@ -1,20 +1,38 @@
+use ability::AbilityPlugin;
 use atoms::{Atom, AtomBundle, AtomPlugin};
 use audio::{AudioMaster, Music, SoundPlugin};
+use budget::BudgetPlugin;
 use bullet::NeutronBundle;
 use bullet::{spawner::WeaponPlugin, RadialVelocity};
 use camera::CameraPlugin;
+use capture::CapturePlugin;
 use collision::CollisionPlugin;
+use debris::DebrisPlugin;
+use delayed::DelayedNeutronPlugin;
 use enemy::spawn_regular;
+use fusion::FusionPlugin;
+use layout::{CoreLayoutConfig, CoreLayoutPlugin};
+use lineage::LineagePlugin;
+use pickup::PickupPlugin;
 use player::{Crosshair, CrosshairOffset, EndGame, PlayerBundle, PlayerPlugin};
 
+use pool::{EntityPool, PoolPlugin};
+use radiation::{RadiationField, RadiationPlugin};
 use rand::Rng;
+use reactor::{Criticality, CriticalityLevel, ReactorPlugin};
+use recording::{FissionRecorder, RecordingPlugin};
 use regular::{RegularPolygons, RegularPolygonsPlugin};
+use rods::{ControlRodLayout, RodPlugin};
+use satellite::SatellitePlugin;
 use shaders::materials::PlayerMaterial;
 use shaders::{ColorPalette, Paper8};
 use shaders::{ShaderArtPlugin, SpaceHaze};
+use shockwave::ShockwavePlugin;
 use std::f32::consts::TAU;
 use std::io::Read;
 use text::{TextPlugin, TypeWriter};
+use upgrade::UpgradePlugin;
+use weapon::InventoryPlugin;
 use winny::ecs::sets::IntoSystemStorage;
 use winny::gfx::camera::Camera;
 use winny::gfx::cgmath::{Quaternion, Rad, Rotation3};
@@ -27,22 +45,39 @@ use winny::{
     prelude::*,
 };
 
+pub mod ability;
 pub mod atoms;
 pub mod audio;
+pub mod budget;
 pub mod bullet;
 pub mod camera;
+pub mod capture;
 pub mod collision;
+pub mod debris;
+pub mod delayed;
 pub mod enemy;
+pub mod fusion;
+pub mod layout;
+pub mod lineage;
 pub mod loader;
 pub mod mouse;
 pub mod pickup;
 pub mod player;
+pub mod pool;
+pub mod radiation;
+pub mod reactor;
+pub mod recording;
 pub mod regular;
+pub mod rods;
+pub mod satellite;
 pub mod shaders;
+pub mod shockwave;
 pub mod text;
 pub mod types;
+pub mod upgrade;
 #[cfg(target_arch = "wasm32")]
 pub mod wasm;
+pub mod weapon;
 
 pub use types::*;
 
@@ -91,7 +126,28 @@ pub fn run() {
             ChildrenPlugin,
             enemy::EnemyPlugin,
             TextPlugin,
-            // pickup::PickupPlugin,
+        ))
+        .add_plugins((
+            RodPlugin,
+            ReactorPlugin,
+            RadiationPlugin,
+            BudgetPlugin,
+            PoolPlugin,
+            ShockwavePlugin,
+            FusionPlugin,
+            DelayedNeutronPlugin,
+            CapturePlugin,
+            LineagePlugin,
+            RecordingPlugin,
+            DebrisPlugin,
+            CoreLayoutPlugin,
+        ))
+        .add_plugins((
+            InventoryPlugin,
+            PickupPlugin,
+            UpgradePlugin,
+            SatellitePlugin,
+            AbilityPlugin,
         ))
         // .insert_resource(TypeWriter::new(
         //     "Meltdown ...".into(),
@@ -158,9 +214,17 @@ impl Default for ThreatLevel {
     }
 }
 
-fn update_threat_level(mut threat: ResMut<ThreatLevel>, game_state: Res<GameState>) {
+fn update_threat_level(
+    mut threat: ResMut<ThreatLevel>,
+    game_state: Res<GameState>,
+    criticality: Res<Criticality>,
+) {
     threat.0 = match *game_state {
-        GameState::Game => 4,
+        GameState::Game => match criticality.level() {
+            CriticalityLevel::Subcritical => 2,
+            CriticalityLevel::Critical => 3,
+            CriticalityLevel::Supercritical | CriticalityLevel::Meltdown => 4,
+        },
         _ => 1,
     }
 }
@@ -222,7 +286,9 @@ fn menu(
 
         let controls = Section::default()
             .add_text(
-                Text::new("Dash   -- Shift\nShoot  -- Space / Left Click")
+                Text::new(
+                    "Dash   -- Shift\nShoot  -- Space / Left Click\nWeapon -- 1-6 / Scroll\nReload -- E\nUpgrade -- Z / X / C\nAbility -- J / K / L\nRods   -- R",
+                )
                     .with_scale(35.0)
                     .with_color(color),
             )
@@ -343,19 +409,23 @@ fn startup(
     mut clear_color: ResMut<ClearColor>,
     mut assets: ResMut<Assets<Mesh2d>>,
     mut audio: ResMut<AudioMaster>,
+    polygons: Res<RegularPolygons>,
+    rod_layout: Res<ControlRodLayout>,
+    layout_config: Res<CoreLayoutConfig>,
     // mut audio: ResMut<GlobalAudio>,
     // type_writer: Res<TypeWriter>,
 ) {
     // type_writer.start(&mut commands);
     // audio.volume = 0.0;
 
-    // #[cfg(not(target_arch = "wasm32"))]
-    // commands.spawn((
-    //     DirWatcherBundle {
-    //         watcher: DirWatcher::new("res").unwrap(),
-    //     },
-    //     WatchForAsset,
-    // ));
+    // reloads pattern files in `res/patterns` when they change
+    #[cfg(not(target_arch = "wasm32"))]
+    match DirWatcher::new("res") {
+        Ok(watcher) => {
+            commands.spawn((DirWatcherBundle { watcher }, WatchForAsset));
+        }
+        Err(e) => error!("failed to watch [res], patterns will not hot reload: {e:?}"),
+    }
 
     // commands.spawn((
     //     SpriteBundle {
@@ -396,6 +466,9 @@ fn startup(
     //     server.load::<Toml, _>("res/emitter.toml"),
     // ));
 
+    commands.insert_resource(Criticality::default());
+    commands.insert_resource(RadiationField::default());
+    commands.insert_resource(FissionRecorder::default());
     commands.spawn(PlayerBundle::new(Vec3f::zero(), &server));
 
     // commands.spawn((NeutronBundle::new_spawner(), Transform::default()));
@@ -431,23 +504,31 @@ fn startup(
 
     // let polygons = RegularPolygons::new(40., &mut assets);
     let mut rng = rand::thread_rng();
-    // for _ in 0..10 {
-    //     let x = rng.gen_range(-500f32..500f32);
-    //     let y = rng.gen_range(-500f32..500f32);
-
-    //     let velocity = Vec3f::new(rng.gen_range(-0.5..0.5), rng.gen_range(-0.5..0.5), 0.);
-
-    //     AtomBundle::spawn(
-    //         &mut commands,
-    //         Vec3f::new(x, y, 0.),
-    //         Some(velocity),
-    //         None,
-    //         &polygons,
-    //         0,
-    //         &server,
-    //         &mut audio,
-    //     );
-    // }
+    let mut pool = EntityPool::default();
+    if layout_config.is_enabled() {
+        let seed = layout_config.next_seed(&mut rng);
+        info!("generating reactor core with seed {seed}");
+
+        let core = layout_config.generate(seed);
+        for position in core.fuel.iter() {
+            AtomBundle::spawn(
+                &mut commands,
+                *position,
+                None,
+                None,
+                &polygons,
+                0,
+                &server,
+                &mut audio,
+                &mut pool,
+            );
+        }
+        layout::spawn_moderators(&mut commands, &core, &layout_config, &polygons);
+        rods::spawn_rods(&mut commands, &core.rods, &polygons);
+    } else {
+        rods::spawn_rods(&mut commands, &rod_layout, &polygons);
+    }
+    commands.insert_resource(pool);
     // spawn_regular(
     //     Default::default(),
     //     &polygons,
@@ -0,0 +1,127 @@
+use crate::{pool::Pooled, should_run_game};
+use winny::{ecs::sets::IntoSystemStorage, prelude::*};
+
+#[derive(Debug)]
+pub struct LineagePlugin;
+
+impl Plugin for LineagePlugin {
+    fn build(&mut self, app: &mut App) {
+        app.insert_resource(LineageConfig::default())
+            .egui_resource::<LineageConfig>()
+            .add_systems(Schedule::Update, age_lineages.run_if(should_run_game));
+    }
+}
+
+/// How many fissions a [Lineage] remembers.
+pub const MAX_LINEAGE_DEPTH: usize = 8;
+
+/// Keeps fission products from immediately re-triggering each other.
+#[derive(Debug, Resource, AsEgui)]
+pub struct LineageConfig {
+    /// Entities that share an ancestor within this many generations don't interact.
+    depth: usize,
+    /// Fission products don't interact with anything for this many seconds after they spawn.
+    immunity: f32,
+}
+
+impl Default for LineageConfig {
+    fn default() -> Self {
+        Self {
+            depth: 2,
+            immunity: 0.15,
+        }
+    }
+}
+
+impl LineageConfig {
+    /// Whether `lineage` is still too young to interact with anything.
+    pub fn is_newborn(&self, lineage: &Lineage) -> bool {
+        lineage.is_newborn(self.immunity)
+    }
+
+    pub fn is_immune(&self, a: &Lineage, b: &Lineage) -> bool {
+        a.is_newborn(self.immunity)
+            || b.is_newborn(self.immunity)
+            || a.shares_ancestor(b, self.depth)
+    }
+}
+
+/// The fissions an atom or neutron descends from, most recent first.
+///
+/// Fissions are identified by their count in [TotalEvents](crate::atoms::TotalEvents) rather
+/// than the split atom, pooled entities are reused and would make unrelated lineages match.
+/// Player shots and placed atoms have an empty lineage and are never immune.
+#[derive(Debug, Default, Clone, Copy, Component)]
+pub struct Lineage {
+    ancestors: [u32; MAX_LINEAGE_DEPTH],
+    len: usize,
+    /// The fission that started the chain reaction.
+    chain: u32,
+    age: f32,
+}
+
+impl Lineage {
+    /// The lineage of a product of the given fission.
+    pub fn child(&self, fission: u32) -> Self {
+        let mut ancestors = [0; MAX_LINEAGE_DEPTH];
+        ancestors[0] = fission;
+        ancestors[1..].copy_from_slice(&self.ancestors[..MAX_LINEAGE_DEPTH - 1]);
+
+        Self {
+            ancestors,
+            len: (self.len + 1).min(MAX_LINEAGE_DEPTH),
+            chain: if self.is_root() { fission } else { self.chain },
+            age: 0.,
+        }
+    }
+
+    /// Like [Lineage::child], but a root lineage joins the chain of `cause`, the neutron that
+    /// split it, instead of starting a new one.
+    pub fn child_by(&self, cause: &Lineage, fission: u32) -> Self {
+        let mut child = self.child(fission);
+        if let (true, Some(chain)) = (self.is_root(), cause.chain()) {
+            child.chain = chain;
+        }
+        child
+    }
+
+    /// The same lineage, but just spawned.
+    pub fn reborn(&self) -> Self {
+        Self { age: 0., ..*self }
+    }
+
+    pub fn is_root(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The chain reaction this lineage belongs to, [None] for entities not spawned by fission.
+    pub fn chain(&self) -> Option<u32> {
+        (!self.is_root()).then_some(self.chain)
+    }
+
+    pub fn age(&self) -> f32 {
+        self.age
+    }
+
+    pub fn ancestors(&self) -> &[u32] {
+        &self.ancestors[..self.len]
+    }
+
+    fn is_newborn(&self, immunity: f32) -> bool {
+        !self.is_root() && self.age < immunity
+    }
+
+    fn shares_ancestor(&self, other: &Self, depth: usize) -> bool {
+        let depth = depth.min(MAX_LINEAGE_DEPTH);
+        let theirs = &other.ancestors()[..other.len.min(depth)];
+        self.ancestors()[..self.len.min(depth)]
+            .iter()
+            .any(|a| theirs.contains(a))
+    }
+}
+
+fn age_lineages(mut lineages: Query<Mut<Lineage>, Without<Pooled>>, delta: Res<DeltaTime>) {
+    for lineage in lineages.iter_mut() {
+        lineage.age += delta.delta;
+    }
+}
@@ -1,28 +1,43 @@
 use crate::{
-    bullet::RadialVelocity,
+    bullet::{
+        expiry::Expiry,
+        spawner::{Lifespan, Uptime},
+        RadialVelocity,
+    },
     collision::{CircleCollider, CollideWithPlayer, Collider, PlayerCollideEvent},
     enemy::random_outside_screen,
-    player::{BulletCount, Player},
+    player::Player,
     regular::RegularPolygons,
     shaders::{materials::HeptaMaterial, Crimson},
+    should_run_game,
+    weapon::Inventory,
 };
 use angle::Radf;
 use camera::Camera;
 use rand::Rng;
 use std::f32::consts::PI;
 use vector::{Vec2f, Vec3f};
-use winny::prelude::*;
+use winny::{ecs::sets::IntoSystemStorage, prelude::*};
 
 #[derive(Debug)]
 pub struct PickupPlugin;
 
 impl Plugin for PickupPlugin {
     fn build(&mut self, app: &mut App) {
-        app.insert_resource(BulletSpawner::default())
-            .add_systems(Schedule::PostUpdate, (spawn_bullet_stuff, player_pickup));
+        app.insert_resource(BulletSpawner::default()).add_systems(
+            Schedule::PostUpdate,
+            (spawn_bullet_stuff, player_pickup).run_if(should_run_game),
+        );
     }
 }
 
+/// Fraction of every weapon's reserve a pickup refills.
+const PICKUP_RESTOCK: f32 = 0.25;
+/// Seconds before an uncollected pickup disappears.
+const PICKUP_LIFESPAN: f32 = 30.;
+/// Seconds an uncollected pickup takes to fade away.
+const PICKUP_FADE: f32 = 3.;
+
 #[derive(Debug, Component)]
 pub struct BulletsPickup;
 
@@ -41,6 +56,9 @@ pub fn spawn_bullets(position: Vec3f, polygons: &RegularPolygons, commands: &mut
             },
             RadialVelocity::new(Radf(PI)),
             BulletsPickup,
+            Lifespan(PICKUP_LIFESPAN),
+            Uptime(0.),
+            Expiry::new(PICKUP_FADE).with_fade().with_shrink(),
             polygons.0[0].clone(),
             HeptaMaterial {
                 modulation: Modulation(Crimson::color(6)),
@@ -84,17 +102,17 @@ fn spawn_bullet_stuff(
 }
 
 fn player_pickup(
-    mut player: Query<(Mut<BulletCount>), With<Player>>,
+    mut player: Query<Mut<Inventory>, With<Player>>,
     pickups: Query<Entity, With<BulletsPickup>>,
     events: EventReader<PlayerCollideEvent>,
     mut commands: Commands,
 ) {
-    let Some(bullets) = player.iter_mut().next() else {
+    let Some(inventory) = player.iter_mut().next() else {
         return;
     };
 
     for event in events.peak_read().filter(|e| pickups.get(e.with).is_some()) {
-        bullets.0 = (bullets.0 + 5).clamp(0, 10);
+        inventory.restock(PICKUP_RESTOCK);
         commands.get_entity(event.with).despawn();
     }
 }
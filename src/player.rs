@@ -1,13 +1,24 @@
 use std::f32::consts::{FRAC_PI_2, TAU};
 
 use crate::{
-    bullet::NeutronBundle,
+    bullet::{
+        expiry::ExpirySpawn,
+        homing::{Homing, HomingConfig},
+        pattern::{Emission, PatternContext},
+        ExtraYield, Neutron, NeutronBundle, Progenitor, NEUTRON_DAMAGE,
+    },
     collision::{
         CircleCollider, CollideWithPlayer, Collider, PlayerCollideEvent, RemoveOnPlayerCollision,
     },
     mouse::MousePosition,
+    pool::EntityPool,
+    radiation::RadiationDose,
+    reactor::{Criticality, SourceNeutron},
     shaders::{materials::PlayerMaterial, Crimson, SpaceHaze},
-    should_run_game, CollisionDamage, Health, Velocity,
+    should_run_game,
+    upgrade::Upgrades,
+    weapon::{ChargeConfig, Inventory, Trigger, Weapon},
+    CollisionDamage, Health, Velocity,
 };
 use winny::{
     asset::server::AssetServer,
@@ -75,9 +86,6 @@ pub struct PlayerExp(pub u32);
 #[derive(Component, Debug, Clone, Copy)]
 pub struct PlayerLevel(pub u32);
 
-#[derive(Component, Debug, Clone, Copy)]
-pub struct BulletCount(pub i32);
-
 impl PlayerLevel {
     pub fn level_up_exp(&self) -> u32 {
         self.0 * 5
@@ -122,7 +130,11 @@ pub struct PlayerBundle {
     last_known_vel: LastKnownVelocity,
     dash: Dash,
     flash: Flash,
-    bullets: BulletCount,
+    dose: RadiationDose,
+    inventory: Inventory,
+    exp: PlayerExp,
+    level: PlayerLevel,
+    upgrades: Upgrades,
 }
 
 impl PlayerBundle {
@@ -157,7 +169,11 @@ impl PlayerBundle {
             material: PlayerMaterial {
                 modulation: Modulation(SpaceHaze::white()),
             },
-            bullets: BulletCount(10),
+            dose: RadiationDose::default(),
+            inventory: Inventory::default(),
+            exp: PlayerExp(0),
+            level: PlayerLevel(1),
+            upgrades: Upgrades::default(),
         }
     }
 
@@ -168,13 +184,6 @@ impl PlayerBundle {
         })
     }
 
-    fn shoot_audio(server: &AssetServer) -> AudioBundle {
-        AudioBundle {
-            handle: server.load("res/RPG_Essentials_Free/10_Battle_SFX/51_Flee_02.wav"),
-            playback_settings: PlaybackSettings::default().with_volume(10.0),
-        }
-    }
-
     fn dash_audio(server: &AssetServer) -> AudioBundle {
         AudioBundle {
             handle: server.load("res/RPG_Essentials_Free/12_Player_Movement_SFX/30_Jump_03.wav"),
@@ -285,9 +294,20 @@ const PLAYER_SPEED: f32 = 5.0;
 
 fn apply_damage(
     mut q: Query<(Mut<Health>, Mut<Flash>), With<Player>>,
-    damage: Query<(CollisionDamage, Option<RemoveOnPlayerCollision>), With<CollideWithPlayer>>,
+    damage: Query<
+        (
+            CollisionDamage,
+            Option<RemoveOnPlayerCollision>,
+            Option<Neutron>,
+            Option<Progenitor>,
+            Option<SourceNeutron>,
+        ),
+        With<CollideWithPlayer>,
+    >,
     reader: EventReader<PlayerCollideEvent>,
     mut commands: Commands,
+    mut pool: ResMut<EntityPool>,
+    mut criticality: ResMut<Criticality>,
 ) {
     let Some((health, flash)) = q.iter_mut().next() else {
         return;
@@ -296,12 +316,20 @@ fn apply_damage(
     const FLASH_DURATION: f32 = 0.1;
 
     for event in reader.peak_read() {
-        if let Some((damage, remove)) = damage.get(event.with) {
+        if let Some((damage, remove, neutron, progenitor, source)) = damage.get(event.with) {
             health.set_current(health.current() - damage.0);
             warn!("hp: {}", health.current());
 
             if remove.is_some() {
-                commands.get_entity(event.with).despawn();
+                if neutron.is_some() {
+                    pool.release_neutron(event.with, true, &mut commands);
+                    // fission neutrons absorbed by the player leave the reactor
+                    if progenitor.is_some_and(|p| p.0.is_some()) && source.is_none() {
+                        criticality.record_losses(1);
+                    }
+                } else {
+                    commands.get_entity(event.with).despawn();
+                }
             }
 
             if flash.0 <= FLASH_DURATION {
@@ -443,16 +471,31 @@ pub struct Crosshair;
 #[derive(Debug, Component)]
 pub struct CrosshairOffset(pub Vec3f);
 
+/// How far the crosshair closes in at full charge.
+const CROSSHAIR_CHARGE_SHRINK: f32 = 0.7;
+
 fn show_crosshair(
-    mut q: Query<(Mut<Transform>, CrosshairOffset), With<Crosshair>>,
+    mut q: Query<(Mut<Transform>, Mut<PlayerMaterial>, CrosshairOffset), With<Crosshair>>,
     mouse_position: Res<MousePosition>,
+    shoot: Res<ShootInfo>,
     window: Res<Window>,
 ) {
     window.winit_window.set_cursor_visible(false);
 
-    for (transform, offset) in q.iter_mut() {
+    let white = SpaceHaze::white();
+    let red = Crimson::color(0);
+    let charge = shoot.charge;
+    let color = Vec4f::new(
+        white.x + (red.x - white.x) * charge,
+        white.y + (red.y - white.y) * charge,
+        white.z + (red.z - white.z) * charge,
+        1.,
+    );
+
+    for (transform, material, offset) in q.iter_mut() {
         let mouse: Vec3f = mouse_position.0.into();
-        transform.translation = mouse + offset.0;
+        transform.translation = mouse + offset.0 * (1. - CROSSHAIR_CHARGE_SHRINK * charge);
+        material.modulation.0 = color;
     }
 }
 
@@ -460,10 +503,12 @@ fn show_crosshair(
 struct ShootInfo {
     active: bool,
     timer: f32,
+    /// How charged the next shot is, in the range [0, 1].
+    charge: f32,
 }
 
 fn watch_click(
-    mut q: Query<(Transform, Velocity, Mut<BulletCount>), With<Player>>,
+    mut q: Query<(Transform, Velocity, Mut<Inventory>), With<Player>>,
     mouse: EventReader<MouseInput>,
     key: EventReader<KeyInput>,
     position: Res<MousePosition>,
@@ -471,8 +516,10 @@ fn watch_click(
     mut shoot: ResMut<ShootInfo>,
     server: Res<AssetServer>,
     delta: Res<DeltaTime>,
+    mut pool: ResMut<EntityPool>,
+    homing: Res<HomingConfig>,
 ) {
-    let Some((transform, _velocity, bullets)) = q.iter_mut().next() else {
+    let Some((transform, _velocity, inventory)) = q.iter_mut().next() else {
         return;
     };
 
@@ -498,37 +545,155 @@ fn watch_click(
         }
     }
 
+    let weapon = inventory.current().clone();
+    let shot_homing = homing.player_homing(weapon.homing);
+    let target: Vec3f = position.0.into();
+
+    if let Some(charge) = weapon.charge {
+        if shoot.active {
+            if inventory.magazine().reload_progress(&weapon.ammo).is_none() {
+                shoot.charge = (shoot.charge + delta.delta / charge.time.max(0.01)).min(1.);
+            }
+            return;
+        }
+
+        // released, fire whatever charge was built up
+        if shoot.charge <= 0. {
+            return;
+        }
+        let level = std::mem::take(&mut shoot.charge);
+        match inventory.pull_trigger() {
+            Trigger::Fired => {}
+            Trigger::Reloading => return,
+            Trigger::Empty => {
+                commands.spawn(Weapon::empty_audio(&server));
+                return;
+            }
+        }
+
+        fire(
+            &weapon,
+            level,
+            aim(transform.translation, target),
+            shot_homing,
+            &server,
+            &mut commands,
+            &mut pool,
+        );
+        return;
+    }
+
+    shoot.charge = 0.;
     if !shoot.active {
         return;
     }
 
     while shoot.timer <= 0. {
-        shoot.timer += 0.25;
-        // if bullets.0 <= 0 {
-        //     continue;
-        // }
-        // bullets.0 -= 1;
-
-        let position: Vec3f = position.0.into();
-        let direction = position - transform.translation;
-        NeutronBundle::spawn(
+        match inventory.pull_trigger() {
+            Trigger::Fired => shoot.timer += weapon.period.max(0.01),
+            Trigger::Reloading => {
+                // fire as soon as the reload finishes, without making up for lost shots
+                shoot.timer = 0.;
+                break;
+            }
+            Trigger::Empty => {
+                commands.spawn(Weapon::empty_audio(&server));
+                shoot.timer += weapon.period.max(0.01);
+                continue;
+            }
+        }
+
+        fire(
+            &weapon,
+            0.,
+            aim(transform.translation, target),
+            shot_homing,
             &server,
-            Transform {
-                translation: transform.translation,
-                scale: Vec2f::one(),
-                ..Default::default()
-            },
-            Velocity(direction.normalize() * 8.),
-            None,
-            false,
             &mut commands,
+            &mut pool,
         );
-        commands.spawn(PlayerBundle::shoot_audio(&server));
     }
 
     shoot.timer -= delta.delta;
 }
 
+fn aim(origin: Vec3f, target: Vec3f) -> PatternContext {
+    let direction = target - origin;
+    PatternContext {
+        origin,
+        target: Some(target),
+        base: direction.y.atan2(direction.x),
+        volley: 0,
+    }
+}
+
+/// Fires one shot of `weapon`, `charge` is ignored by weapons that don't charge.
+fn fire(
+    weapon: &Weapon,
+    charge: f32,
+    context: PatternContext,
+    (homing, turn_rate): (Homing, f32),
+    server: &AssetServer,
+    commands: &mut Commands,
+    pool: &mut EntityPool,
+) {
+    let origin = context.origin;
+    weapon.pattern().fire(context, &mut |emission| {
+        let Emission::Shot { direction, speed } = emission else {
+            return;
+        };
+
+        let mut transform = Transform {
+            translation: origin,
+            scale: Vec2f::one(),
+            ..Default::default()
+        };
+        let mut speed = speed;
+        if let Some(config) = weapon.charge {
+            speed *= ChargeConfig::scale(config.speed, charge);
+        }
+
+        let neutron = NeutronBundle::spawn(
+            server,
+            transform,
+            Velocity(Vec3f::new(direction.cos(), direction.sin(), 0.) * speed),
+            None,
+            false,
+            commands,
+            pool,
+        );
+        commands.get_entity(neutron).insert(weapon.hit_budget);
+        if homing.is_homing() {
+            homing.attach(neutron, turn_rate, commands);
+        }
+
+        if let Some(config) = weapon.charge {
+            // spawning resets the scale, so grow the neutron afterwards
+            let size = 0.1 * ChargeConfig::scale(config.size, charge);
+            transform.scale = Vec2f::new(size, size);
+            commands
+                .get_entity(neutron)
+                .insert(transform)
+                .insert(CollisionDamage(
+                    NEUTRON_DAMAGE * ChargeConfig::scale(config.damage, charge),
+                ))
+                .insert(ExtraYield(config.extra_neutrons(charge)));
+
+            // a charged shot that hits nothing still releases its neutrons
+            let extra = config.extra_neutrons(charge) as u32;
+            if extra > 0 {
+                commands
+                    .get_entity(neutron)
+                    .insert(NeutronBundle::expiry().with_spawn(ExpirySpawn::Burst {
+                        count: extra,
+                        speed,
+                    }));
+            }
+        }
+    });
+    commands.spawn(weapon.audio(server));
+}
+
 #[derive(Event)]
 pub struct EndGame;
 
@@ -544,159 +709,3 @@ pub fn check_for_player_death(
         writer.send(EndGame);
     }
 }
-
-// pub fn update_player_ui(
-//     player: Query<(Entity, Transform, Health, PlayerExp, PlayerLevel), With<Player>>,
-//     mut health: Query<Mut<Sprite>, With<PlayerHealthBar>>,
-//     mut xp: Query<Mut<Sprite>, With<PlayerXpBar>>,
-//     mut text_renderer: Option<ResMut<TextRenderer>>,
-//     input: EventReader<KeyInput>,
-//     context: Res<RenderContext>,
-//     mut commands: Commands,
-//     mut audio_master: ResMut<AudioMaster>,
-// ) {
-//     let Some((player, transform, player_health, PlayerExp(player_xp), level)) =
-//         player.iter().next()
-//     else {
-//         return;
-//     };
-//
-//     for event in input.peak_read() {
-//         match event {
-//             KeyInput {
-//                 code: KeyCode::J,
-//                 state: KeyState::Pressed,
-//             } => {
-//                 crate::weapons::aoe::AoeAttack::NecroCircle.spawn_hit_enemy(
-//                     player,
-//                     transform.translation,
-//                     &mut commands,
-//                     &mut audio_master,
-//                 );
-//             }
-//             KeyInput {
-//                 code: KeyCode::K,
-//                 state: KeyState::Pressed,
-//             } => {
-//                 crate::weapons::aoe::AoeAttack::NecroSpin.spawn_hit_enemy(
-//                     player,
-//                     transform.translation,
-//                     &mut commands,
-//                     &mut audio_master,
-//                 );
-//             }
-//             KeyInput {
-//                 code: KeyCode::L,
-//                 state: KeyState::Pressed,
-//             } => {
-//                 crate::weapons::aoe::AoeAttack::BloodBurst.spawn_hit_enemy(
-//                     player,
-//                     transform.translation,
-//                     &mut commands,
-//                     &mut audio_master,
-//                 );
-//             }
-//             _ => {}
-//         }
-//     }
-//
-//     for bar in health.iter_mut() {
-//         let ratio = player_health.ratio();
-//         bar.scale.v[0] = ratio;
-//         bar.position.x = -(256. * (1. - ratio)) * 0.5 * UI_SCALE;
-//     }
-//
-//     for bar in xp.iter_mut() {
-//         let ratio = *player_xp as f32 / level.level_up_exp() as f32;
-//         bar.scale.v[0] = ratio;
-//         bar.position.x = -(256. * (1. - ratio)) * 0.5 * UI_SCALE;
-//     }
-//
-//     let Some(text_renderer) = &mut text_renderer else {
-//         return;
-//     };
-//
-//     use winny::gfx::wgpu_text::glyph_brush::*;
-//
-//     let level_text = format!("{}", level.0);
-//     text_renderer.draw(&context.device, &context.queue, || {
-//         let xp = Section::default()
-//             .add_text(
-//                 Text::new(&level_text)
-//                     .with_scale(20.0)
-//                     .with_color([1., 1., 1., 1.]),
-//             )
-//             .with_bounds((
-//                 context.config.width() as f32,
-//                 context.config.height() as f32,
-//             ))
-//             .with_screen_position((150., 110.))
-//             .with_layout(
-//                 Layout::default()
-//                     .h_align(HorizontalAlign::Center)
-//                     .v_align(VerticalAlign::Center),
-//             );
-//
-//         vec![xp]
-//     });
-// }
-
-// fn level_up_sfx(mut master: ResMut<AudioMaster>, lvl_up: EventReader<LevelUpEvent>) {
-//     if lvl_up.peak().is_some() {
-//         let sound_path = "013_Confirm_03.wav";
-//         let bundle = AudioBundle {
-//             handle: master.get_handle_or_dangle(&AudioPath(sound_path)),
-//             playback_settings: PlaybackSettings {
-//                 volume: 5.0,
-//                 ..Default::default()
-//             },
-//         };
-//
-//         master.queue_bundle(bundle);
-//     }
-// }
-//
-// fn handle_player_level_up(
-//     mut commands: Commands,
-//     player: Query<Entity, With<Player>>,
-//     lvl_up: EventReader<LevelUpEvent>,
-// ) {
-//     let Ok(player) = player.get_single() else {
-//         return;
-//     };
-//
-//     for lvl_up in lvl_up.read() {
-//         let level = lvl_up.0;
-//         match level.0 {
-//             1 => {
-//                 // commands.spawn((
-//                 //     FireBallBundle::new_spawner(),
-//                 //     Transform::default(),
-//                 //     ChildOf(player),
-//                 // ));
-//             }
-//             2 => {
-//                 commands.spawn((
-//                     FireSkullBundle::new_spawner(),
-//                     Transform::default(),
-//                     ChildOf(player),
-//                 ));
-//             }
-//             3 => {
-//                 commands.spawn((
-//                     AoeAttack::NecroCircle.new_spawner(1.5),
-//                     Transform::default(),
-//                     ChildOf(player),
-//                 ));
-//             }
-//             4 => {
-//                 commands.spawn((
-//                     AoeAttack::NecroSpin.new_spawner(1.),
-//                     Transform::default(),
-//                     ChildOf(player),
-//                 ));
-//             }
-//             _ => {}
-//         }
-//     }
-// }
@@ -0,0 +1,355 @@
+use crate::{bullet::EnemyProjectile, reactor::SourceNeutron, ChildOffset, Parent, Velocity};
+use fxhash::FxHashSet;
+use vector::{Vec2f, Vec3f};
+use winny::prelude::*;
+#[cfg(debug_assertions)]
+use {crate::should_run_game, winny::ecs::sets::IntoSystemStorage};
+
+#[derive(Debug)]
+pub struct PoolPlugin;
+
+impl Plugin for PoolPlugin {
+    fn build(&mut self, app: &mut App) {
+        app.insert_resource(EntityPool::default())
+            .add_systems(Schedule::PreUpdate, recycle_released);
+
+        #[cfg(debug_assertions)]
+        app.insert_resource(benchmark::PoolBenchmark::default())
+            .add_systems(
+                Schedule::Update,
+                benchmark::run_benchmark.run_if(should_run_game),
+            );
+    }
+}
+
+/// Marks an entity that is parked in the [EntityPool].
+///
+/// Pooled entities keep their components, but are moved off screen, shrunk to nothing
+/// and should be ignored by every system that deals with live atoms and neutrons.
+#[derive(Debug, Component, Clone, Copy)]
+pub struct Pooled;
+
+const NEUTRON_PARK: Vec3f = Vec3f::new(-1_000_000., -1_000_000., 0.);
+const ATOM_PARK: Vec3f = Vec3f::new(1_000_000., 1_000_000., 0.);
+
+/// Recycles deactivated atom and neutron entities instead of despawning them.
+///
+/// Atoms are pooled per generation because each generation uses a different material,
+/// neutrons are pooled by whether they can hit the player.
+///
+/// Entities released during a frame can only be taken again the next frame, a system still
+/// holding one from its query can then release it twice without parking an entity that was
+/// already reused.
+#[derive(Debug, Resource)]
+pub struct EntityPool {
+    enabled: bool,
+    atoms: [Vec<Entity>; 7],
+    neutrons: [Vec<Entity>; 2],
+    /// Released this frame, with the index of the pool they go back to.
+    released_atoms: Vec<(usize, Entity)>,
+    released_neutrons: Vec<(usize, Entity)>,
+    /// Every parked entity, whether it can be taken yet or not.
+    pooled: FxHashSet<Entity>,
+}
+
+impl Default for EntityPool {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            atoms: Default::default(),
+            neutrons: Default::default(),
+            released_atoms: Vec::new(),
+            released_neutrons: Vec::new(),
+            pooled: FxHashSet::default(),
+        }
+    }
+}
+
+impl EntityPool {
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Disabling the pool forgets every parked entity, they are left to be despawned with the level.
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+        if !enabled {
+            self.clear();
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.atoms.iter_mut().for_each(Vec::clear);
+        self.neutrons.iter_mut().for_each(Vec::clear);
+        self.released_atoms.clear();
+        self.released_neutrons.clear();
+        self.pooled.clear();
+    }
+
+    /// Makes the entities released since the last call available to take.
+    fn recycle(&mut self) {
+        for (events, entity) in self.released_atoms.drain(..) {
+            self.atoms[events].push(entity);
+        }
+        for (hits_player, entity) in self.released_neutrons.drain(..) {
+            self.neutrons[hits_player].push(entity);
+        }
+    }
+
+    pub fn take_atom(&mut self, events: u32) -> Option<Entity> {
+        let entity = self.atoms.get_mut(events as usize)?.pop()?;
+        self.pooled.remove(&entity);
+        Some(entity)
+    }
+
+    pub fn take_neutron(&mut self, hits_player: bool) -> Option<Entity> {
+        let entity = self.neutrons[hits_player as usize].pop()?;
+        self.pooled.remove(&entity);
+        Some(entity)
+    }
+
+    /// Parks an atom for reuse, or despawns it if the pool is disabled.
+    ///
+    /// Releasing a parked entity is a no-op.
+    pub fn release_atom(&mut self, entity: Entity, events: u32, commands: &mut Commands) {
+        if !self.enabled || events as usize >= self.atoms.len() {
+            commands.get_entity(entity).despawn();
+            return;
+        }
+
+        if !self.pooled.insert(entity) {
+            return;
+        }
+
+        self.released_atoms.push((events as usize, entity));
+        commands
+            .get_entity(entity)
+            .insert(Pooled)
+            .insert(Self::parked(ATOM_PARK))
+            .insert(Velocity::default())
+            .remove::<Parent>()
+            .remove::<ChildOffset>();
+    }
+
+    /// Parks a neutron for reuse, or despawns it if the pool is disabled.
+    ///
+    /// Releasing a parked entity is a no-op.
+    pub fn release_neutron(&mut self, entity: Entity, hits_player: bool, commands: &mut Commands) {
+        if !self.enabled {
+            commands.get_entity(entity).despawn();
+            return;
+        }
+
+        if !self.pooled.insert(entity) {
+            return;
+        }
+
+        self.released_neutrons.push((hits_player as usize, entity));
+        commands
+            .get_entity(entity)
+            .insert(Pooled)
+            .insert(Self::parked(NEUTRON_PARK))
+            .insert(Velocity::default())
+            .remove::<EnemyProjectile>()
+            .remove::<SourceNeutron>();
+    }
+
+    fn parked(translation: Vec3f) -> Transform {
+        Transform {
+            translation,
+            scale: Vec2f::zero(),
+            ..Default::default()
+        }
+    }
+}
+
+fn recycle_released(mut pool: ResMut<EntityPool>) {
+    pool.recycle();
+}
+
+/// Only in debug builds, it despawns every atom and neutron in the level.
+#[cfg(debug_assertions)]
+mod benchmark {
+    use super::EntityPool;
+    use crate::{
+        atoms::{Atom, AtomBundle},
+        audio::AudioMaster,
+        bullet::{Neutron, NeutronBundle},
+        player::Player,
+        regular::RegularPolygons,
+        Velocity,
+    };
+    use server::AssetServer;
+    use std::f32::consts::TAU;
+    use vector::{Vec2f, Vec3f};
+    use winny::prelude::*;
+
+    /// Compares frame times during a large chain reaction with and without pooling.
+    ///
+    /// Press `P` in game to run it, the results are logged.
+    #[derive(Debug, Default, Resource)]
+    pub(super) struct PoolBenchmark {
+        running: Option<bool>,
+        elapsed: f32,
+        frame_times: Vec<f32>,
+        results: Vec<(bool, FrameStats)>,
+        was_enabled: bool,
+    }
+
+    const BENCHMARK_DURATION: f32 = 8.0;
+
+    #[derive(Debug, Clone, Copy)]
+    struct FrameStats {
+        mean: f32,
+        p95: f32,
+        max: f32,
+    }
+
+    impl FrameStats {
+        fn new(frame_times: &mut [f32]) -> Self {
+            frame_times.sort_by(f32::total_cmp);
+            let len = frame_times.len().max(1);
+
+            Self {
+                mean: frame_times.iter().sum::<f32>() / len as f32 * 1000.,
+                p95: frame_times.get(len * 95 / 100).copied().unwrap_or_default() * 1000.,
+                max: frame_times.last().copied().unwrap_or_default() * 1000.,
+            }
+        }
+    }
+
+    /// Spawns a dense grid of atoms and fires a ring of neutrons into it.
+    fn seed_chain_reaction(
+        center: Vec3f,
+        commands: &mut Commands,
+        pool: &mut EntityPool,
+        polygons: &RegularPolygons,
+        server: &AssetServer,
+        audio: &mut AudioMaster,
+    ) {
+        const GRID: i32 = 12;
+        const SPACING: f32 = 70.;
+
+        for x in -GRID / 2..GRID / 2 {
+            for y in -GRID / 2..GRID / 2 {
+                AtomBundle::spawn(
+                    commands,
+                    center + Vec3f::new(x as f32 * SPACING, y as f32 * SPACING, 0.),
+                    None,
+                    None,
+                    polygons,
+                    0,
+                    server,
+                    audio,
+                    pool,
+                );
+            }
+        }
+
+        for i in 0..16 {
+            let angle = i as f32 / 16. * TAU;
+            NeutronBundle::spawn(
+                server,
+                Transform {
+                    translation: center,
+                    scale: Vec2f::one(),
+                    ..Default::default()
+                },
+                Velocity(Vec3f::new(angle.cos(), angle.sin(), 0.) * 4.),
+                None,
+                false,
+                commands,
+                pool,
+            );
+        }
+    }
+
+    pub(super) fn run_benchmark(
+        mut commands: Commands,
+        mut benchmark: ResMut<PoolBenchmark>,
+        mut pool: ResMut<EntityPool>,
+        input: EventReader<KeyInput>,
+        player: Query<Transform, With<Player>>,
+        atoms: Query<Entity, With<Atom>>,
+        neutrons: Query<Entity, With<Neutron>>,
+        polygons: Res<RegularPolygons>,
+        server: Res<AssetServer>,
+        mut audio: ResMut<AudioMaster>,
+        delta: Res<DeltaTime>,
+    ) {
+        let Ok(player) = player.get_single() else {
+            return;
+        };
+        let center = player.translation + Vec3f::new(600., 0., 0.);
+
+        let Some(pooled) = benchmark.running else {
+            if input.peak_read().any(|k| {
+                matches!(
+                    k,
+                    KeyInput {
+                        code: KeyCode::P,
+                        state: KeyState::Pressed,
+                        ..
+                    }
+                )
+            }) {
+                info!("starting pool benchmark");
+                benchmark.was_enabled = pool.is_enabled();
+                benchmark.results.clear();
+                benchmark.running = Some(true);
+                pool.set_enabled(true);
+                seed_chain_reaction(
+                    center,
+                    &mut commands,
+                    &mut pool,
+                    &polygons,
+                    &server,
+                    &mut audio,
+                );
+            }
+            return;
+        };
+
+        benchmark.elapsed += delta.delta;
+        benchmark.frame_times.push(delta.delta);
+
+        if benchmark.elapsed < BENCHMARK_DURATION {
+            return;
+        }
+
+        let stats = FrameStats::new(&mut benchmark.frame_times);
+        benchmark.results.push((pooled, stats));
+        benchmark.frame_times.clear();
+        benchmark.elapsed = 0.0;
+
+        // clear the field so both runs start from the same state
+        for entity in atoms.iter().chain(neutrons.iter()) {
+            commands.get_entity(entity).despawn();
+        }
+        pool.clear();
+
+        if pooled {
+            benchmark.running = Some(false);
+            pool.set_enabled(false);
+            seed_chain_reaction(
+                center,
+                &mut commands,
+                &mut pool,
+                &polygons,
+                &server,
+                &mut audio,
+            );
+        } else {
+            benchmark.running = None;
+            let was_enabled = benchmark.was_enabled;
+            pool.set_enabled(was_enabled);
+
+            for (pooled, stats) in benchmark.results.iter() {
+                info!(
+                    "pool benchmark [pooling: {pooled}]: mean {:.2}ms, p95 {:.2}ms, max {:.2}ms",
+                    stats.mean, stats.p95, stats.max
+                );
+            }
+        }
+    }
+}
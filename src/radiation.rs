@@ -0,0 +1,159 @@
+use crate::{
+    atoms::FissionEvent,
+    player::{Flash, Player},
+    should_run_game, Health,
+};
+use vector::Vec3f;
+use winny::{ecs::sets::IntoSystemStorage, prelude::*};
+
+#[derive(Debug)]
+pub struct RadiationPlugin;
+
+impl Plugin for RadiationPlugin {
+    fn build(&mut self, app: &mut App) {
+        app.insert_resource(RadiationField::default())
+            .insert_resource(RadiationConfig::default())
+            .egui_resource::<RadiationConfig>()
+            .add_systems(
+                Schedule::PostUpdate,
+                apply_radiation.run_if(should_run_game),
+            );
+    }
+}
+
+/// Tuning for the radiation released by fission.
+#[derive(Debug, Resource, AsEgui)]
+pub struct RadiationConfig {
+    /// Intensity released by a single fission at its center.
+    source_strength: f32,
+    /// How long a fission keeps irradiating its surroundings, in seconds.
+    source_lifetime: f32,
+    /// Distance at which a source's intensity has halved.
+    falloff_radius: f32,
+    /// Dose the player can absorb before taking damage.
+    dose_threshold: f32,
+    /// Health lost per second, per unit of dose above the threshold.
+    damage_per_dose: f32,
+    /// Dose shed per second.
+    recovery_rate: f32,
+}
+
+impl Default for RadiationConfig {
+    fn default() -> Self {
+        Self {
+            source_strength: 1.0,
+            source_lifetime: 1.5,
+            falloff_radius: 150.0,
+            dose_threshold: 5.0,
+            damage_per_dose: 0.2,
+            recovery_rate: 0.5,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct RadiationSource {
+    position: Vec3f,
+    strength: f32,
+    age: f32,
+}
+
+/// Recent fission events that irradiate anything close to them.
+#[derive(Debug, Default, Resource)]
+pub struct RadiationField {
+    sources: Vec<RadiationSource>,
+}
+
+impl RadiationField {
+    /// Adds a new source of radiation. `strength` is relative to [RadiationConfig]'s source strength.
+    pub fn emit(&mut self, position: Vec3f, strength: f32) {
+        self.sources.push(RadiationSource {
+            position,
+            strength,
+            age: 0.0,
+        });
+    }
+
+    /// Sums the intensity of every source at `position`.
+    ///
+    /// Sources fade linearly over their lifetime and fall off with the square of the distance.
+    pub fn intensity_at(&self, position: &Vec3f, config: &RadiationConfig) -> f32 {
+        self.sources
+            .iter()
+            .map(|source| {
+                let remaining = 1.0 - source.age / config.source_lifetime;
+                let falloff = 1.0 + source.position.dist2(position) / config.falloff_radius.powi(2);
+                config.source_strength * source.strength * remaining / falloff
+            })
+            .sum()
+    }
+
+    fn age(&mut self, config: &RadiationConfig, dt: f32) {
+        for source in self.sources.iter_mut() {
+            source.age += dt;
+        }
+        self.sources.retain(|s| s.age < config.source_lifetime);
+    }
+
+    pub fn clear(&mut self) {
+        self.sources.clear();
+    }
+}
+
+/// The radiation the player has absorbed.
+///
+/// Kept separate from [Health]: dose builds up while standing near fission and
+/// only starts hurting once it passes [RadiationConfig]'s threshold.
+#[derive(Debug, Default, Component, Clone, Copy, PartialEq)]
+pub struct RadiationDose {
+    dose: f32,
+    intensity: f32,
+}
+
+impl RadiationDose {
+    pub fn dose(&self) -> f32 {
+        self.dose
+    }
+
+    /// The intensity of the field the player is standing in.
+    pub fn intensity(&self) -> f32 {
+        self.intensity
+    }
+
+    /// The dose relative to the damage threshold. Values above 1 hurt.
+    pub fn ratio(&self, config: &RadiationConfig) -> f32 {
+        self.dose / config.dose_threshold
+    }
+}
+
+pub(crate) fn emit_radiation(reader: EventReader<FissionEvent>, mut field: ResMut<RadiationField>) {
+    for event in reader.peak_read() {
+        field.emit(event.position, 1.0);
+    }
+}
+
+fn apply_radiation(
+    mut field: ResMut<RadiationField>,
+    config: Res<RadiationConfig>,
+    mut player: Query<(Transform, Mut<RadiationDose>, Mut<Health>, Mut<Flash>), With<Player>>,
+    delta: Res<DeltaTime>,
+) {
+    field.age(&config, delta.delta);
+
+    let Some((transform, dose, health, flash)) = player.iter_mut().next() else {
+        return;
+    };
+
+    dose.intensity = field.intensity_at(&transform.translation, &config);
+    dose.dose = (dose.dose + (dose.intensity - config.recovery_rate) * delta.delta).max(0.0);
+
+    let excess = dose.dose - config.dose_threshold;
+    if excess > 0.0 {
+        health.set_current(health.current() - excess * config.damage_per_dose * delta.delta);
+
+        const FLASH_DURATION: f32 = 0.05;
+        if flash.0 <= FLASH_DURATION {
+            flash.0 = FLASH_DURATION;
+        }
+    }
+}
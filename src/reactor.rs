@@ -0,0 +1,188 @@
+use crate::{bullet::NEUTRON_LIFESPAN, player::EndGame, should_run_game};
+use std::collections::VecDeque;
+use winny::{ecs::sets::IntoSystemStorage, prelude::*};
+
+#[derive(Debug)]
+pub struct ReactorPlugin;
+
+impl Plugin for ReactorPlugin {
+    fn build(&mut self, app: &mut App) {
+        app.insert_resource(Criticality::default())
+            .insert_resource(ReactorConfig::default())
+            .egui_resource::<ReactorConfig>()
+            .register_event::<CriticalityEvent>()
+            .add_systems(
+                Schedule::PostUpdate,
+                (update_criticality, meltdown).run_if(should_run_game),
+            );
+    }
+}
+
+/// Tuning for the criticality estimate.
+#[derive(Debug, Resource, AsEgui)]
+pub struct ReactorConfig {
+    /// Length of the sliding window k is estimated over, in seconds.
+    ///
+    /// Never shorter than a neutron's lifespan, otherwise births leave the window before the
+    /// neutrons they count are lost.
+    window: f32,
+    /// k at which the reactor is considered critical.
+    critical: f32,
+    /// k at which the player is warned.
+    supercritical: f32,
+    /// k that will melt the reactor down if sustained.
+    meltdown: f32,
+    /// How long k must stay above `meltdown` before the reactor melts down, in seconds.
+    meltdown_duration: f32,
+}
+
+impl Default for ReactorConfig {
+    fn default() -> Self {
+        Self {
+            window: NEUTRON_LIFESPAN * 2.,
+            critical: 0.9,
+            supercritical: 1.2,
+            meltdown: 1.5,
+            meltdown_duration: 5.0,
+        }
+    }
+}
+
+/// How close the reactor is to running away, ordered from least to most dangerous.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum CriticalityLevel {
+    #[default]
+    Subcritical,
+    Critical,
+    Supercritical,
+    Meltdown,
+}
+
+/// Sent whenever the reactor escalates to a more dangerous [CriticalityLevel].
+#[derive(Debug, Clone, Copy, Event)]
+pub struct CriticalityEvent(pub CriticalityLevel);
+
+/// Marks neutrons released by a fission that was started from outside the reactor.
+///
+/// Their births are not part of the chain, so neither are their losses.
+#[derive(Debug, Clone, Copy, Component)]
+pub struct SourceNeutron;
+
+#[derive(Debug, Clone, Copy)]
+struct NeutronSample {
+    time: f32,
+    births: u32,
+    losses: u32,
+}
+
+/// Estimates the neutron multiplication factor k from the neutrons born and lost over
+/// a sliding window.
+///
+/// Only neutrons produced by fission are counted, the player's shots are an external source
+/// and so are the neutrons of the fissions they start, which carry a [SourceNeutron] so their
+/// losses are skipped like their births.
+#[derive(Debug, Default, Resource)]
+pub struct Criticality {
+    k: f32,
+    level: CriticalityLevel,
+    elapsed: f32,
+    births: u32,
+    losses: u32,
+    samples: VecDeque<NeutronSample>,
+    meltdown_timer: f32,
+}
+
+impl Criticality {
+    pub fn record_births(&mut self, count: u32) {
+        self.births += count;
+    }
+
+    pub fn record_losses(&mut self, count: u32) {
+        self.losses += count;
+    }
+
+    pub fn k(&self) -> f32 {
+        self.k
+    }
+
+    pub fn level(&self) -> CriticalityLevel {
+        self.level
+    }
+
+    /// Progress towards a meltdown in the range [0, 1].
+    pub fn meltdown_progress(&self, config: &ReactorConfig) -> f32 {
+        (self.meltdown_timer / config.meltdown_duration).clamp(0.0, 1.0)
+    }
+
+    /// Pushes this frame's births and losses into the window and re-estimates k.
+    ///
+    /// Returns the new level if the reactor escalated.
+    fn update(&mut self, config: &ReactorConfig, dt: f32) -> Option<CriticalityLevel> {
+        self.elapsed += dt;
+        self.samples.push_back(NeutronSample {
+            time: self.elapsed,
+            births: std::mem::take(&mut self.births),
+            losses: std::mem::take(&mut self.losses),
+        });
+
+        let window = config.window.max(NEUTRON_LIFESPAN);
+        while self
+            .samples
+            .front()
+            .is_some_and(|s| self.elapsed - s.time > window)
+        {
+            self.samples.pop_front();
+        }
+
+        let (births, losses) = self
+            .samples
+            .iter()
+            .fold((0, 0), |(b, l), s| (b + s.births, l + s.losses));
+        self.k = births as f32 / losses.max(1) as f32;
+
+        if self.level == CriticalityLevel::Meltdown {
+            return None;
+        }
+
+        if self.k >= config.meltdown {
+            self.meltdown_timer += dt;
+        } else {
+            self.meltdown_timer = (self.meltdown_timer - dt).max(0.0);
+        }
+
+        let level = if self.meltdown_timer >= config.meltdown_duration {
+            CriticalityLevel::Meltdown
+        } else if self.k >= config.supercritical {
+            CriticalityLevel::Supercritical
+        } else if self.k >= config.critical {
+            CriticalityLevel::Critical
+        } else {
+            CriticalityLevel::Subcritical
+        };
+
+        let escalated = level > self.level;
+        self.level = level;
+        escalated.then_some(level)
+    }
+}
+
+fn update_criticality(
+    mut criticality: ResMut<Criticality>,
+    config: Res<ReactorConfig>,
+    mut writer: EventWriter<CriticalityEvent>,
+    delta: Res<DeltaTime>,
+) {
+    if let Some(level) = criticality.update(&config, delta.delta) {
+        warn!("reactor is {level:?}: k = {:.2}", criticality.k());
+        writer.send(CriticalityEvent(level));
+    }
+}
+
+fn meltdown(reader: EventReader<CriticalityEvent>, mut writer: EventWriter<EndGame>) {
+    if reader
+        .peak_read()
+        .any(|e| e.0 == CriticalityLevel::Meltdown)
+    {
+        writer.send(EndGame);
+    }
+}
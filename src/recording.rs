@@ -0,0 +1,210 @@
+#[cfg(not(target_arch = "wasm32"))]
+use crate::player::EndGame;
+use crate::{atoms::FissionEvent, should_run_game};
+use vector::Vec3f;
+use winny::{ecs::sets::IntoSystemStorage, prelude::*};
+
+#[derive(Debug)]
+pub struct RecordingPlugin;
+
+impl Plugin for RecordingPlugin {
+    fn build(&mut self, app: &mut App) {
+        app.insert_resource(FissionRecorder::default())
+            .insert_resource(RecordingConfig::default())
+            .egui_resource::<RecordingConfig>()
+            .add_systems(Schedule::Update, tick_recorder.run_if(should_run_game));
+
+        // the game state changes to death when the game ends, so this can't be gated on it
+        #[cfg(not(target_arch = "wasm32"))]
+        app.add_systems(Schedule::PostUpdate, export_recording);
+    }
+}
+
+/// Where and how a run's fissions are exported.
+#[derive(Debug, Resource, AsEgui)]
+pub struct RecordingConfig {
+    enabled: bool,
+    directory: String,
+    /// Width and height of the heatmap in pixels.
+    heatmap_size: u32,
+    /// Radius of the splat each fission adds to the heatmap, in pixels.
+    splat_radius: f32,
+}
+
+impl Default for RecordingConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            directory: "recordings".into(),
+            heatmap_size: 512,
+            splat_radius: 6.,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct FissionRecord {
+    /// Seconds since the run started.
+    pub time: f32,
+    pub position: Vec3f,
+    pub generation: u32,
+    pub chain: u32,
+}
+
+/// Every fission of the current run.
+#[derive(Debug, Default, Resource)]
+pub struct FissionRecorder {
+    elapsed: f32,
+    records: Vec<FissionRecord>,
+}
+
+impl FissionRecorder {
+    pub fn record(&mut self, position: Vec3f, generation: u32, chain: u32) {
+        self.records.push(FissionRecord {
+            time: self.elapsed,
+            position,
+            generation,
+            chain,
+        });
+    }
+
+    pub fn records(&self) -> &[FissionRecord] {
+        &self.records
+    }
+}
+
+fn tick_recorder(mut recorder: ResMut<FissionRecorder>, delta: Res<DeltaTime>) {
+    recorder.elapsed += delta.delta;
+}
+
+pub(crate) fn record_fissions(
+    reader: EventReader<FissionEvent>,
+    mut recorder: ResMut<FissionRecorder>,
+) {
+    for event in reader.peak_read() {
+        recorder.record(event.position, event.generation, event.chain_id);
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn export_recording(
+    reader: EventReader<EndGame>,
+    recorder: Res<FissionRecorder>,
+    config: Res<RecordingConfig>,
+) {
+    if reader.peak().is_none() || !config.enabled || recorder.records.is_empty() {
+        return;
+    }
+
+    let stamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs());
+    let path = std::path::Path::new(&config.directory);
+
+    if let Err(e) = std::fs::create_dir_all(path) {
+        error!("failed to create [{}]: {e}", config.directory);
+        return;
+    }
+
+    let csv = path.join(format!("fissions_{stamp}.csv"));
+    match std::fs::write(&csv, to_csv(&recorder.records)) {
+        Ok(_) => info!(
+            "wrote {} fissions to {}",
+            recorder.records.len(),
+            csv.display()
+        ),
+        Err(e) => error!("failed to write [{}]: {e}", csv.display()),
+    }
+
+    let png = path.join(format!("heatmap_{stamp}.png"));
+    let size = config.heatmap_size.max(1);
+    let pixels = heatmap(&recorder.records, size, config.splat_radius);
+    match write_png(&png, size, &pixels) {
+        Ok(_) => info!("wrote heatmap to {}", png.display()),
+        Err(e) => error!("failed to write [{}]: {e}", png.display()),
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn to_csv(records: &[FissionRecord]) -> String {
+    let mut csv = String::from("time,x,y,generation,chain\n");
+    for r in records.iter() {
+        csv.push_str(&format!(
+            "{:.3},{:.1},{:.1},{},{}\n",
+            r.time, r.position.x, r.position.y, r.generation, r.chain
+        ));
+    }
+    csv
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+/// Splats every fission onto a square image that covers all of them, returns RGB pixels.
+fn heatmap(records: &[FissionRecord], size: u32, radius: f32) -> Vec<u8> {
+    let (min, max) = records.iter().fold(
+        (
+            Vec3f::new(f32::MAX, f32::MAX, 0.),
+            Vec3f::new(f32::MIN, f32::MIN, 0.),
+        ),
+        |(min, max), r| {
+            (
+                Vec3f::new(min.x.min(r.position.x), min.y.min(r.position.y), 0.),
+                Vec3f::new(max.x.max(r.position.x), max.y.max(r.position.y), 0.),
+            )
+        },
+    );
+
+    // keep the aspect ratio and leave room for the splats at the edges
+    let extent = (max.x - min.x).max(max.y - min.y).max(1.);
+    let padding = radius * 2.;
+    let scale = (size as f32 - padding * 2.).max(1.) / extent;
+
+    let mut heat = vec![0f32; (size * size) as usize];
+    let reach = radius.ceil() as i32 * 2;
+    for r in records.iter() {
+        let cx = padding + (r.position.x - min.x) * scale;
+        // world y points up, image rows go down
+        let cy = size as f32 - (padding + (r.position.y - min.y) * scale);
+
+        for dy in -reach..=reach {
+            for dx in -reach..=reach {
+                let x = cx as i32 + dx;
+                let y = cy as i32 + dy;
+                if x < 0 || y < 0 || x >= size as i32 || y >= size as i32 {
+                    continue;
+                }
+
+                let d2 = (x as f32 - cx).powi(2) + (y as f32 - cy).powi(2);
+                heat[(y as u32 * size + x as u32) as usize] += (-d2 / (2. * radius * radius)).exp();
+            }
+        }
+    }
+
+    let peak = heat.iter().copied().fold(0f32, f32::max).max(f32::EPSILON);
+    heat.iter().flat_map(|h| ramp((h / peak).sqrt())).collect()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+/// Black through purple and orange to white.
+fn ramp(t: f32) -> [u8; 3] {
+    const STOPS: [[f32; 3]; 4] = [
+        [0., 0., 0.],
+        [0.45, 0.1, 0.6],
+        [1., 0.55, 0.1],
+        [1., 1., 1.],
+    ];
+
+    let t = t.clamp(0., 1.) * (STOPS.len() - 1) as f32;
+    let i = (t.floor() as usize).min(STOPS.len() - 2);
+    let f = t - i as f32;
+    let (a, b) = (STOPS[i], STOPS[i + 1]);
+    [0, 1, 2].map(|c| ((a[c] + (b[c] - a[c]) * f) * 255.).round() as u8)
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn write_png(path: &std::path::Path, size: u32, pixels: &[u8]) -> Result<(), png::EncodingError> {
+    let file = std::io::BufWriter::new(std::fs::File::create(path)?);
+    let mut encoder = png::Encoder::new(file, size, size);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder.write_header()?.write_image_data(pixels)
+}
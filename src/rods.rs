@@ -0,0 +1,241 @@
+use crate::{
+    bullet::{pierce::HitBudget, Neutron, Progenitor},
+    collision::{CircleCollider, CollideWithPlayer, Collider, EnemyCollideEvent},
+    pool::{EntityPool, Pooled},
+    reactor::{Criticality, SourceNeutron},
+    regular::RegularPolygons,
+    shaders::{materials::QuadrilateralMaterial, SpaceHaze},
+    should_run_game, CollisionDamage, Enemy, Velocity,
+};
+use mesh2d::Mesh2d;
+use server::AssetServer;
+use std::f32::consts::FRAC_PI_4;
+use vector::{Vec2f, Vec3f};
+use winny::{
+    ecs::sets::IntoSystemStorage,
+    gfx::cgmath::{Quaternion, Rad, Rotation3},
+    prelude::*,
+};
+
+#[derive(Debug)]
+pub struct RodPlugin;
+
+impl Plugin for RodPlugin {
+    fn build(&mut self, app: &mut App) {
+        app.insert_resource(ControlRodLayout::default())
+            .insert_resource(RodControl::default())
+            .egui_resource::<RodControl>()
+            .add_systems(Schedule::Update, toggle_rods.run_if(should_run_game))
+            .add_systems(
+                Schedule::PostUpdate,
+                absorb_neutrons.run_if(should_run_game),
+            );
+    }
+}
+
+/// Absorbs any neutron that touches it without triggering fission.
+#[derive(Debug, Component, Clone, Copy)]
+pub struct ControlRod {
+    deployed: bool,
+    retractable: bool,
+}
+
+impl ControlRod {
+    pub fn is_deployed(&self) -> bool {
+        self.deployed
+    }
+}
+
+const ROD_RADIUS: f32 = 45.;
+
+#[derive(Bundle)]
+pub struct ControlRodBundle {
+    rod: ControlRod,
+    enemy: Enemy,
+    transform: Transform,
+    collider: Collider,
+    mesh: Handle<Mesh2d>,
+    material: QuadrilateralMaterial,
+}
+
+impl ControlRodBundle {
+    pub fn new(placement: &RodPlacement, polygons: &RegularPolygons) -> Self {
+        Self {
+            rod: ControlRod {
+                deployed: true,
+                retractable: placement.retractable,
+            },
+            enemy: Enemy,
+            transform: Transform {
+                translation: placement.position,
+                rotation: Quaternion::from_angle_z(Rad(FRAC_PI_4)),
+                scale: Vec2f::new(1.0, 1.0),
+            },
+            collider: Self::collider(),
+            mesh: polygons.0[1].clone(),
+            material: QuadrilateralMaterial {
+                modulation: Modulation(SpaceHaze::white()),
+            },
+        }
+    }
+
+    fn collider() -> Collider {
+        Collider::Circle(CircleCollider {
+            position: Vec3f::zero(),
+            radius: ROD_RADIUS,
+        })
+    }
+
+    fn toggle_audio(server: &AssetServer) -> AudioBundle {
+        AudioBundle {
+            handle: server.load("res/RPG_Essentials_Free/10_Battle_SFX/39_Block_03.wav"),
+            playback_settings: PlaybackSettings::default().with_volume(10.0),
+        }
+    }
+}
+
+/// Where a control rod should be placed when a level starts.
+#[derive(Debug, Clone, Copy)]
+pub struct RodPlacement {
+    pub position: Vec3f,
+    /// Whether the player is allowed to retract and redeploy this rod.
+    pub retractable: bool,
+}
+
+/// The control rods spawned at the start of a run.
+#[derive(Debug, Clone, Resource)]
+pub struct ControlRodLayout(pub Vec<RodPlacement>);
+
+impl Default for ControlRodLayout {
+    fn default() -> Self {
+        let rod = |x: f32, y: f32, retractable: bool| RodPlacement {
+            position: Vec3f::new(x, y, 0.),
+            retractable,
+        };
+
+        Self(vec![
+            rod(-300., -300., true),
+            rod(300., -300., true),
+            rod(-300., 300., true),
+            rod(300., 300., true),
+            rod(0., 550., false),
+            rod(0., -550., false),
+        ])
+    }
+}
+
+pub fn spawn_rods(commands: &mut Commands, layout: &ControlRodLayout, polygons: &RegularPolygons) {
+    for placement in layout.0.iter() {
+        commands.spawn(ControlRodBundle::new(placement, polygons));
+    }
+}
+
+/// Player control over the retractable rods.
+#[derive(Debug, Resource, AsEgui)]
+pub struct RodControl {
+    cooldown_duration: f32,
+    cooldown: f32,
+}
+
+impl Default for RodControl {
+    fn default() -> Self {
+        Self {
+            cooldown_duration: 3.0,
+            cooldown: 0.0,
+        }
+    }
+}
+
+fn toggle_rods(
+    mut commands: Commands,
+    input: EventReader<KeyInput>,
+    mut control: ResMut<RodControl>,
+    mut rods: Query<(Entity, Mut<ControlRod>, Mut<QuadrilateralMaterial>)>,
+    server: Res<AssetServer>,
+    delta: Res<DeltaTime>,
+) {
+    control.cooldown = (control.cooldown - delta.delta).max(0.0);
+
+    let pressed = input.peak_read().any(|k| {
+        matches!(
+            k,
+            KeyInput {
+                code: KeyCode::R,
+                state: KeyState::Pressed,
+                ..
+            }
+        )
+    });
+
+    if !pressed || control.cooldown > 0.0 {
+        return;
+    }
+
+    control.cooldown = control.cooldown_duration;
+    commands.spawn(ControlRodBundle::toggle_audio(&server));
+
+    for (entity, rod, material) in rods.iter_mut().filter(|(_, r, _)| r.retractable) {
+        rod.deployed = !rod.deployed;
+
+        if rod.deployed {
+            commands
+                .get_entity(entity)
+                .insert(ControlRodBundle::collider());
+            material.modulation.0 = SpaceHaze::white();
+        } else {
+            commands.get_entity(entity).remove::<Collider>();
+            material.modulation.0 = SpaceHaze::purple();
+        }
+    }
+}
+
+fn absorb_neutrons(
+    rods: Query<(ControlRod, Transform)>,
+    neutrons: Query<
+        (
+            Progenitor,
+            Option<SourceNeutron>,
+            Option<CollideWithPlayer>,
+            Transform,
+            Velocity,
+            CollisionDamage,
+            HitBudget,
+        ),
+        (With<Neutron>, Without<Pooled>),
+    >,
+    reader: EventReader<EnemyCollideEvent>,
+    mut commands: Commands,
+    mut criticality: ResMut<Criticality>,
+    mut pool: ResMut<EntityPool>,
+) {
+    for event in reader.peak_read() {
+        let Some((_, rod)) = rods.get(event.enemy).filter(|(r, _)| r.deployed) else {
+            continue;
+        };
+
+        if let Some((progenitor, source, hits_player, transform, velocity, damage, hit_budget)) =
+            neutrons.get(event.with)
+        {
+            // deployed rods are walls to ricochets
+            let mut budget = *hit_budget;
+            let (mut velocity, mut damage) = (velocity.0, damage.0);
+            if budget.ricochets()
+                && budget.spend(
+                    event.with,
+                    transform.translation,
+                    &mut velocity,
+                    &mut damage,
+                    rod.translation,
+                    &mut commands,
+                )
+            {
+                continue;
+            }
+
+            pool.release_neutron(event.with, hits_player.is_some(), &mut commands);
+            if progenitor.0.is_some() && source.is_none() {
+                criticality.record_losses(1);
+            }
+        }
+    }
+}
@@ -0,0 +1,203 @@
+use crate::{
+    bullet::{pattern_file::BundledPattern, EnemyProjectile, Neutron, Progenitor},
+    player::Player,
+    pool::{EntityPool, Pooled},
+    reactor::{Criticality, SourceNeutron},
+    regular::RegularPolygons,
+    shaders::{materials::HeptaMaterial, Crimson, SpaceHaze},
+    should_run_game,
+    upgrade::Upgrades,
+    ChildOffset, Parent,
+};
+use std::f32::consts::TAU;
+use winny::{
+    asset::server::AssetServer, ecs::sets::IntoSystemStorage, math::vector::Vec3f, prelude::*,
+};
+
+#[derive(Debug)]
+pub struct SatellitePlugin;
+
+impl Plugin for SatellitePlugin {
+    fn build(&mut self, app: &mut App) {
+        app.insert_resource(SatelliteConfig::default())
+            .insert_resource(Orbit::default())
+            .egui_resource::<SatelliteConfig>()
+            .add_systems(
+                Schedule::Update,
+                (sync_satellites, orbit_satellites, block_projectiles).run_if(should_run_game),
+            );
+    }
+}
+
+/// Satellites orbiting the player, their number grows with the orbiter upgrades.
+#[derive(Debug, Resource, AsEgui)]
+pub struct SatelliteConfig {
+    /// Satellites the player has before any upgrades.
+    count: u32,
+    radius: f32,
+    /// Radians per second.
+    speed: f32,
+    /// [EnemyProjectile]s closer than this to a shield are destroyed.
+    block_radius: f32,
+}
+
+impl Default for SatelliteConfig {
+    fn default() -> Self {
+        Self {
+            count: 0,
+            radius: 90.,
+            speed: 2.,
+            block_radius: 24.,
+        }
+    }
+}
+
+/// The angle of the first satellite, the rest are spaced evenly after it.
+#[derive(Debug, Default, Resource)]
+struct Orbit(f32);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SatelliteKind {
+    /// Destroys enemy projectiles it touches.
+    Shield,
+    /// Fires homing neutrons at nearby enemies.
+    Turret,
+}
+
+impl SatelliteKind {
+    /// Satellites alternate between shields and turrets, starting with a shield.
+    fn for_index(index: u32) -> Self {
+        if index % 2 == 0 {
+            Self::Shield
+        } else {
+            Self::Turret
+        }
+    }
+}
+
+/// An entity orbiting the player, attached with [Parent] and [ChildOffset].
+#[derive(Debug, Clone, Copy, Component)]
+pub struct Satellite {
+    index: u32,
+    kind: SatelliteKind,
+}
+
+impl Satellite {
+    fn spawn(
+        player: Entity,
+        index: u32,
+        polygons: &RegularPolygons,
+        server: &AssetServer,
+        commands: &mut Commands,
+    ) {
+        let kind = SatelliteKind::for_index(index);
+        let (mesh, color) = match kind {
+            SatelliteKind::Shield => (polygons.0[3].clone(), SpaceHaze::white()),
+            SatelliteKind::Turret => (polygons.0[0].clone(), Crimson::color(0)),
+        };
+
+        let satellite = commands
+            .spawn((
+                Satellite { index, kind },
+                Transform::default(),
+                Parent(player),
+                ChildOffset(Vec3f::zero()),
+                mesh,
+                HeptaMaterial {
+                    modulation: Modulation(color),
+                },
+            ))
+            .entity();
+
+        if kind == SatelliteKind::Turret {
+            BundledPattern::RICOCHET.attach(satellite, server, commands);
+        }
+    }
+}
+
+/// Spawns or despawns satellites until the player has as many as they should.
+fn sync_satellites(
+    player: Query<(Entity, Upgrades), With<Player>>,
+    satellites: Query<(Entity, Satellite)>,
+    config: Res<SatelliteConfig>,
+    polygons: Res<RegularPolygons>,
+    server: Res<AssetServer>,
+    mut commands: Commands,
+) {
+    let Ok((player, upgrades)) = player.get_single() else {
+        return;
+    };
+
+    let wanted = config.count + upgrades.orbiters();
+    let current = satellites.iter().count() as u32;
+
+    for index in current..wanted {
+        Satellite::spawn(player, index, &polygons, &server, &mut commands);
+    }
+
+    for (entity, _) in satellites.iter().filter(|(_, s)| s.index >= wanted) {
+        commands.get_entity(entity).despawn();
+    }
+}
+
+fn orbit_satellites(
+    mut satellites: Query<(Satellite, Mut<ChildOffset>)>,
+    mut orbit: ResMut<Orbit>,
+    config: Res<SatelliteConfig>,
+    delta: Res<DeltaTime>,
+) {
+    orbit.0 = (orbit.0 + config.speed * delta.delta) % TAU;
+
+    let count = satellites.iter().count().max(1) as f32;
+    for (satellite, offset) in satellites.iter_mut() {
+        let angle = orbit.0 + satellite.index as f32 / count * TAU;
+        offset.0 = Vec3f::new(angle.cos(), angle.sin(), 0.) * config.radius;
+    }
+}
+
+fn block_projectiles(
+    satellites: Query<(Satellite, Transform)>,
+    projectiles: Query<
+        (
+            Entity,
+            Transform,
+            Option<Neutron>,
+            Option<Progenitor>,
+            Option<SourceNeutron>,
+        ),
+        (With<EnemyProjectile>, Without<Pooled>),
+    >,
+    config: Res<SatelliteConfig>,
+    mut commands: Commands,
+    mut pool: ResMut<EntityPool>,
+    mut criticality: ResMut<Criticality>,
+) {
+    let block_radius = config.block_radius * config.block_radius;
+    let shields = satellites
+        .iter()
+        .filter(|(s, _)| s.kind == SatelliteKind::Shield)
+        .map(|(_, t)| t.translation)
+        .collect::<Vec<_>>();
+    if shields.is_empty() {
+        return;
+    }
+
+    for (projectile, transform, neutron, progenitor, source) in projectiles.iter() {
+        if !shields
+            .iter()
+            .any(|shield| transform.translation.dist2(shield) <= block_radius)
+        {
+            continue;
+        }
+
+        if neutron.is_none() {
+            commands.get_entity(projectile).despawn();
+            continue;
+        }
+
+        pool.release_neutron(projectile, true, &mut commands);
+        if progenitor.is_some_and(|p| p.0.is_some()) && source.is_none() {
+            criticality.record_losses(1);
+        }
+    }
+}
@@ -3,6 +3,11 @@ use winny::{
     prelude::*,
 };
 
+/// A material tinted by a [Modulation], implemented by every material in this module.
+pub trait Modulated {
+    fn modulation_mut(&mut self) -> &mut Modulation;
+}
+
 macro_rules! impl_material {
     ($ty:ident, $raw_ty:ident) => {
         #[derive(Component, AsEgui, Debug, Clone)]
@@ -65,6 +70,12 @@ macro_rules! impl_material {
             const VISIBILITY: &'static [wgpu::ShaderStages] = &[wgpu::ShaderStages::FRAGMENT];
         }
 
+        impl Modulated for $ty {
+            fn modulation_mut(&mut self) -> &mut Modulation {
+                &mut self.modulation
+            }
+        }
+
         impl $ty {
             pub(crate) fn as_raw(&self) -> $raw_ty {
                 $raw_ty {
@@ -92,3 +103,4 @@ impl_material!(QuadrilateralMaterial, RawQuadrilateralMaterial);
 impl_material!(TriangleMaterial, RawTriangleMaterial);
 impl_material!(PlayerMaterial, RawPlayerMaterial);
 impl_material!(NeutronMaterial, RawNeutronMaterial);
+impl_material!(DebrisMaterial, RawDebrisMaterial);
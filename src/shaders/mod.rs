@@ -7,8 +7,8 @@ use crate::{
 use self::{
     downscale::Pixler,
     materials::{
-        HeptaMaterial, HexaMaterial, NeutronMaterial, NonagonMaterial, OctagonMaterial,
-        PentagonMaterial, PlayerMaterial, QuadrilateralMaterial, TriangleMaterial,
+        DebrisMaterial, HeptaMaterial, HexaMaterial, NeutronMaterial, NonagonMaterial,
+        OctagonMaterial, PentagonMaterial, PlayerMaterial, QuadrilateralMaterial, TriangleMaterial,
     },
     post_processing::{
         bloom_binding, build_post_processing_pipeline_with_binding,
@@ -42,6 +42,7 @@ impl Plugin for ShaderArtPlugin {
             .add_plugins(MaterialPlugin::<TriangleMaterial>::new())
             .add_plugins(MaterialPlugin::<PlayerMaterial>::new())
             .add_plugins(MaterialPlugin::<NeutronMaterial>::new())
+            .add_plugins(MaterialPlugin::<DebrisMaterial>::new())
             .register_resource::<PostProcessingPipeline<BrightnessThreshold>>()
             .register_resource::<PostProcessingPipeline<GaussianBlurH>>()
             .register_resource::<PostProcessingPipeline<GaussianBlurV>>()
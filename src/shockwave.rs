@@ -0,0 +1,218 @@
+use crate::{
+    atoms::FissionEvent,
+    collision::{
+        spatial::{SpatialData, SpatialHash},
+        AbsoluteCollider, CircleCollider,
+    },
+    player::Flash,
+    pool::Pooled,
+    should_run_game, Health, Velocity,
+};
+use fxhash::FxHashSet;
+use server::AssetServer;
+use vector::{Vec2f, Vec3f};
+use winny::{
+    ecs::sets::IntoSystemStorage,
+    gfx::{
+        render_pipeline::material::Material2d,
+        sprite::{Sprite, SpriteBundle},
+    },
+    prelude::*,
+};
+
+#[derive(Debug)]
+pub struct ShockwavePlugin;
+
+impl Plugin for ShockwavePlugin {
+    fn build(&mut self, app: &mut App) {
+        app.insert_resource(ShockwaveConfig::default())
+            .egui_resource::<ShockwaveConfig>()
+            .add_systems(Schedule::Update, expand_shockwaves.run_if(should_run_game));
+    }
+}
+
+/// Tuning for the shockwave released by a generation 0 fission. Later generations are weaker.
+#[derive(Debug, Resource, AsEgui)]
+pub struct ShockwaveConfig {
+    max_radius: f32,
+    /// How fast the ring expands, in pixels per second.
+    speed: f32,
+    /// Velocity added to anything the ring passes over.
+    push: f32,
+    damage: f32,
+    /// How much weaker each generation is, in the range [0, 1].
+    generation_falloff: f32,
+}
+
+impl Default for ShockwaveConfig {
+    fn default() -> Self {
+        Self {
+            max_radius: 250.,
+            speed: 700.,
+            push: 3.,
+            damage: 2.,
+            generation_falloff: 0.15,
+        }
+    }
+}
+
+impl ShockwaveConfig {
+    fn scale(&self, generation: u32) -> f32 {
+        (1.0 - self.generation_falloff * generation as f32).max(0.1)
+    }
+}
+
+/// An expanding ring released by fission.
+///
+/// Pushes entities with a [Velocity] outward and damages entities with [Health], the player
+/// included, both falling off as the ring grows. Each entity is only hit once per ring.
+#[derive(Debug, Component)]
+pub struct Shockwave {
+    generation: u32,
+    radius: f32,
+    pushed: FxHashSet<Entity>,
+    damaged: FxHashSet<Entity>,
+}
+
+#[derive(Bundle)]
+pub struct ShockwaveBundle {
+    shockwave: Shockwave,
+    transform: Transform,
+}
+
+impl ShockwaveBundle {
+    pub fn new(position: Vec3f, generation: u32, server: &AssetServer) -> (Self, SpriteBundle) {
+        (
+            Self {
+                shockwave: Shockwave {
+                    generation,
+                    radius: 0.,
+                    pushed: FxHashSet::default(),
+                    damaged: FxHashSet::default(),
+                },
+                transform: Transform {
+                    translation: position,
+                    ..Default::default()
+                },
+            },
+            SpriteBundle {
+                material: Material2d {
+                    texture: server.load("res/textures/circle.png"),
+                    ..Default::default()
+                },
+                sprite: Sprite {
+                    position: Vec3f::zero(),
+                    scale: Vec2f::zero(),
+                    z: 900,
+                    ..Default::default()
+                },
+            },
+        )
+    }
+}
+
+pub(crate) fn spawn_shockwaves(
+    reader: EventReader<FissionEvent>,
+    mut commands: Commands,
+    server: Res<AssetServer>,
+) {
+    for event in reader.peak_read() {
+        commands.spawn(ShockwaveBundle::new(
+            event.position,
+            event.generation,
+            &server,
+        ));
+    }
+}
+
+fn expand_shockwaves(
+    mut commands: Commands,
+    mut waves: Query<(Entity, Transform, Mut<Shockwave>, Mut<Sprite>)>,
+    mut pushed: Query<(Entity, Transform, Mut<Velocity>), Without<Pooled>>,
+    mut damaged: Query<(Entity, Transform, Mut<Health>)>,
+    mut flashes: Query<Mut<Flash>>,
+    config: Res<ShockwaveConfig>,
+    delta: Res<DeltaTime>,
+) {
+    // waves never grow past `max_radius`, so with cells that wide a wave only needs to look at
+    // the cells around its center
+    let cell_size = config.max_radius.max(1.);
+    let mut pushable = SpatialHash::new(cell_size);
+    for (target, transform, _) in pushed.iter() {
+        pushable.insert(point(target, transform.translation));
+    }
+    let mut damageable = SpatialHash::new(cell_size);
+    for (target, transform, _) in damaged.iter() {
+        damageable.insert(point(target, transform.translation));
+    }
+
+    for (entity, wave_transform, wave, sprite) in waves.iter_mut() {
+        let scale = config.scale(wave.generation);
+        let max_radius = config.max_radius * scale;
+
+        wave.radius += config.speed * delta.delta;
+        if wave.radius >= max_radius {
+            commands.get_entity(entity).despawn();
+            continue;
+        }
+
+        // the circle texture is 256 pixels wide
+        let sprite_scale = wave.radius / 128.;
+        sprite.scale = Vec2f::new(sprite_scale, sprite_scale);
+
+        let falloff = 1.0 - wave.radius / max_radius;
+        let center = wave_transform.translation;
+        let radius2 = wave.radius.powi(2);
+
+        for SpatialData {
+            entity: target,
+            position,
+            ..
+        } in pushable.nearby_objects(&center)
+        {
+            if position.dist2(&center) > radius2 || !wave.pushed.insert(*target) {
+                continue;
+            }
+
+            let offset = *position - center;
+            if let Some((_, _, velocity)) = pushed.get_mut(*target) {
+                if !offset.is_zero() {
+                    velocity.0 += offset.normalize() * config.push * scale * falloff;
+                }
+            }
+        }
+
+        for SpatialData {
+            entity: target,
+            position,
+            ..
+        } in damageable.nearby_objects(&center)
+        {
+            if position.dist2(&center) > radius2 || !wave.damaged.insert(*target) {
+                continue;
+            }
+
+            if let Some((_, _, health)) = damaged.get_mut(*target) {
+                health.set_current(health.current() - config.damage * scale * falloff);
+            }
+
+            if let Some(flash) = flashes.get_mut(*target) {
+                const FLASH_DURATION: f32 = 0.1;
+                if flash.0 <= FLASH_DURATION {
+                    flash.0 = FLASH_DURATION;
+                }
+            }
+        }
+    }
+}
+
+fn point(entity: Entity, position: Vec3f) -> SpatialData {
+    SpatialData {
+        entity,
+        position,
+        collider: AbsoluteCollider::Circle(CircleCollider {
+            position,
+            radius: 0.,
+        }),
+    }
+}
@@ -1,7 +1,18 @@
 use std::ops::Range;
 use winny::{ecs::sets::IntoSystemStorage, math::vector::Vec2f, prelude::*};
 
-use crate::{atoms::TotalEvents, player::Player, should_run_game, Health};
+use crate::{
+    ability::Abilities,
+    atoms::TotalEvents,
+    budget::EntityBudget,
+    player::{Player, PlayerExp, PlayerLevel},
+    radiation::{RadiationConfig, RadiationDose},
+    reactor::{Criticality, CriticalityLevel, ReactorConfig},
+    should_run_game,
+    upgrade::{UpgradeTree, Upgrades, CHOICE_KEYS},
+    weapon::Inventory,
+    Health,
+};
 
 #[derive(Debug)]
 pub struct TextPlugin;
@@ -108,10 +119,26 @@ fn display_health(
     context: Res<RenderContext>,
     mut text_renderer: ResMut<TextRenderer>,
     fission: Res<TotalEvents>,
-    player: Query<Health, With<Player>>,
+    criticality: Res<Criticality>,
+    reactor: Res<ReactorConfig>,
+    radiation: Res<RadiationConfig>,
+    budget: Res<EntityBudget>,
+    tree: Res<UpgradeTree>,
+    abilities: Res<Abilities>,
+    player: Query<
+        (
+            Health,
+            RadiationDose,
+            Inventory,
+            PlayerExp,
+            PlayerLevel,
+            Upgrades,
+        ),
+        With<Player>,
+    >,
 ) {
     use winny::gfx::wgpu_text::glyph_brush::*;
-    let Ok(player_health) = player.get_single() else {
+    let Ok((player_health, dose, inventory, exp, level, upgrades)) = player.get_single() else {
         return;
     };
 
@@ -127,8 +154,115 @@ fn display_health(
     }
     string.push_str("]");
 
+    let mut rads = String::new();
+    rads.push_str("rad: [");
+    let dose_ratio = dose.ratio(&radiation);
+    let absorbed = (dose_ratio.min(1.0) * 10.0) as usize;
+    for i in 0..10 {
+        if absorbed > i {
+            rads.push_str(" ~ ");
+        } else {
+            rads.push_str("   ");
+        }
+    }
+    rads.push_str("]");
+    let rad_color: [f32; 4] = if dose_ratio >= 1.0 {
+        [1.0, 0.1, 0.2, 1.0]
+    } else if dose.intensity() > 0.0 && dose_ratio > 0.0 {
+        [0.4, 1.0, 0.3, 1.0]
+    } else {
+        [1.0, 1.0, 1.0, 1.0]
+    };
+
     let events = format!("Fission: {}", fission.0);
 
+    let weapons = inventory
+        .weapons()
+        .iter()
+        .enumerate()
+        .map(|(i, weapon)| {
+            if i == inventory.current_index() {
+                format!("[{} {}]", i + 1, weapon.name)
+            } else {
+                format!(" {} {} ", i + 1, weapon.name)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("  ");
+
+    let weapon = inventory.current();
+    let magazine = inventory.magazine();
+    let ammo = if weapon.ammo.is_infinite() {
+        String::from("ammo: inf")
+    } else if let Some(progress) = magazine.reload_progress(&weapon.ammo) {
+        format!("reloading {:.0}%", progress * 100.0)
+    } else {
+        format!(
+            "ammo: {} / {}  reserve: {}",
+            magazine.loaded(),
+            weapon.ammo.magazine,
+            magazine.reserve()
+        )
+    };
+    let ammo_color: [f32; 4] = if magazine.is_flashing_empty() {
+        [1.0, 0.1, 0.2, 1.0]
+    } else {
+        [1.0, 1.0, 1.0, 1.0]
+    };
+
+    let mut progress = format!("lvl {}  exp: {} / {}", level.0, exp.0, level.level_up_exp());
+    if upgrades.pending() > 1 {
+        progress.push_str(&format!("  ({} upgrades waiting)", upgrades.pending()));
+    }
+    let offer = upgrades
+        .offer()
+        .iter()
+        .zip(CHOICE_KEYS)
+        .map(|(index, key)| {
+            let node = tree.node(*index);
+            format!("[{key:?}] {} - {}", node.name, node.description)
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let abilities = abilities
+        .iter()
+        .map(|(ability, cooldown)| {
+            if cooldown > 0. {
+                format!("{:?} {} {:.1}s", ability.key, ability.name, cooldown)
+            } else {
+                format!("{:?} {} ready", ability.key, ability.name)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("   ");
+
+    let readout = format!(
+        "atoms: {}\nneutrons: {}\nbudget: {} / {} (soft {})\npressure: {:.0}%",
+        budget.atoms(),
+        budget.neutrons(),
+        budget.total(),
+        budget.hard_limit(),
+        budget.soft_limit(),
+        budget.pressure() * 100.0,
+    );
+
+    let k = match criticality.level() {
+        CriticalityLevel::Subcritical | CriticalityLevel::Critical => {
+            format!("k: {:.2}", criticality.k())
+        }
+        CriticalityLevel::Supercritical | CriticalityLevel::Meltdown => format!(
+            "k: {:.2}  SUPERCRITICAL {:.0}%",
+            criticality.k(),
+            criticality.meltdown_progress(&reactor) * 100.0
+        ),
+    };
+    let k_color: [f32; 4] = match criticality.level() {
+        CriticalityLevel::Subcritical => [1.0, 1.0, 1.0, 1.0],
+        CriticalityLevel::Critical => [1.0, 0.8, 0.2, 1.0],
+        CriticalityLevel::Supercritical | CriticalityLevel::Meltdown => [1.0, 0.1, 0.2, 1.0],
+    };
+
     text_renderer.draw(&context, || {
         let color: [f32; 4] = [1.0, 1.0, 1.0, 1.0];
         let middle = Section::default()
@@ -153,6 +287,122 @@ fn display_health(
                     .v_align(VerticalAlign::Center),
             );
 
-        vec![middle, fission]
+        let criticality = Section::default()
+            .add_text(Text::new(&k).with_scale(20.).with_color(k_color))
+            .with_screen_position((context.config.width() as f32 / 2.0, 85.0))
+            .with_layout(
+                Layout::default()
+                    .h_align(HorizontalAlign::Center)
+                    .v_align(VerticalAlign::Center),
+            );
+
+        let radiation = Section::default()
+            .add_text(Text::new(&rads).with_scale(20.).with_color(rad_color))
+            .with_screen_position((context.config.width() as f32 / 2.0, 110.0))
+            .with_layout(
+                Layout::default()
+                    .h_align(HorizontalAlign::Center)
+                    .v_align(VerticalAlign::Center),
+            );
+
+        let abilities = Section::default()
+            .add_text(Text::new(&abilities).with_scale(20.).with_color(color))
+            .with_screen_position((context.config.width() as f32 / 2.0, 135.0))
+            .with_layout(
+                Layout::default()
+                    .h_align(HorizontalAlign::Center)
+                    .v_align(VerticalAlign::Center),
+            );
+
+        let weapons = Section::default()
+            .add_text(Text::new(&weapons).with_scale(20.).with_color(color))
+            .with_screen_position((
+                context.config.width() as f32 / 2.0,
+                context.config.height() as f32 - 30.0,
+            ))
+            .with_layout(
+                Layout::default()
+                    .h_align(HorizontalAlign::Center)
+                    .v_align(VerticalAlign::Center),
+            );
+
+        let ammo = Section::default()
+            .add_text(Text::new(&ammo).with_scale(20.).with_color(ammo_color))
+            .with_screen_position((
+                context.config.width() as f32 / 2.0,
+                context.config.height() as f32 - 55.0,
+            ))
+            .with_layout(
+                Layout::default()
+                    .h_align(HorizontalAlign::Center)
+                    .v_align(VerticalAlign::Center),
+            );
+
+        let progress = Section::default()
+            .add_text(Text::new(&progress).with_scale(20.).with_color(color))
+            .with_screen_position((
+                context.config.width() as f32 / 2.0,
+                context.config.height() as f32 - 80.0,
+            ))
+            .with_layout(
+                Layout::default()
+                    .h_align(HorizontalAlign::Center)
+                    .v_align(VerticalAlign::Center),
+            );
+
+        let mut sections = vec![
+            middle,
+            fission,
+            criticality,
+            radiation,
+            abilities,
+            weapons,
+            ammo,
+            progress,
+        ];
+
+        if !offer.is_empty() {
+            sections.push(
+                Section::default()
+                    .add_text(
+                        Text::new(&offer)
+                            .with_scale(20.)
+                            .with_color([1.0, 0.8, 0.2, 1.0]),
+                    )
+                    .with_screen_position((
+                        context.config.width() as f32 / 2.0,
+                        context.config.height() as f32 - 110.0,
+                    ))
+                    .with_layout(
+                        Layout::default()
+                            .h_align(HorizontalAlign::Center)
+                            .v_align(VerticalAlign::Bottom),
+                    ),
+            );
+        }
+
+        if budget.show_readout() {
+            let readout_color: [f32; 4] = if budget.total() > budget.hard_limit() {
+                [1.0, 0.1, 0.2, 1.0]
+            } else {
+                color
+            };
+            sections.push(
+                Section::default()
+                    .add_text(
+                        Text::new(&readout)
+                            .with_scale(18.)
+                            .with_color(readout_color),
+                    )
+                    .with_screen_position((20.0, 20.0))
+                    .with_layout(
+                        Layout::default()
+                            .h_align(HorizontalAlign::Left)
+                            .v_align(VerticalAlign::Top),
+                    ),
+            );
+        }
+
+        sections
     });
 }
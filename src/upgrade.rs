@@ -0,0 +1,226 @@
+//! Upgrades offered to the player on level-up, the tree is read from `res/upgrades.toml`.
+//!
+//! Experience is earned from fissions and kills. Every level-up offers a few upgrades whose
+//! requirements have been taken, one is picked with [CHOICE_KEYS] while the game keeps running.
+
+use crate::{
+    atoms::FissionEvent,
+    enemy::EnemyKilled,
+    player::{Player, PlayerExp, PlayerLevel},
+    should_run_game,
+    weapon::Inventory,
+};
+use rand::seq::SliceRandom;
+use serde::{de::Error as _, Deserialize};
+use std::f32::consts::PI;
+use winny::{ecs::sets::IntoSystemStorage, prelude::*};
+
+#[derive(Debug)]
+pub struct UpgradePlugin;
+
+impl Plugin for UpgradePlugin {
+    fn build(&mut self, app: &mut App) {
+        let tree = UpgradeTree::parse(include_str!("../res/upgrades.toml"))
+            .unwrap_or_else(|e| panic!("invalid upgrade tree: {e}"));
+
+        app.insert_resource(tree)
+            .add_systems(Schedule::Update, choose_upgrade.run_if(should_run_game));
+    }
+}
+
+/// Fissions it takes to earn one experience point, chain reactions split a lot of atoms.
+const FISSIONS_PER_EXP: u32 = 10;
+const KILL_EXP: u32 = 5;
+/// Picks the first, second and third offered upgrade.
+pub const CHOICE_KEYS: [KeyCode; 3] = [KeyCode::Z, KeyCode::X, KeyCode::C];
+/// Radians a weapon's spread widens by for every projectile split off its shots.
+const SPLIT_SPREAD: f32 = PI * 0.06;
+
+/// What an upgrade does to the player's weapons.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Modifier {
+    /// Multiplies how often every weapon fires.
+    FireRate(f32),
+    /// Extra hits every shot survives.
+    Pierce(u32),
+    /// Extra projectiles per shot.
+    Split(u32),
+    /// Extra satellites circling the player.
+    Orbiters(u32),
+}
+
+impl Modifier {
+    fn apply(&self, inventory: &mut Inventory, upgrades: &mut Upgrades) {
+        for weapon in inventory.weapons_mut() {
+            match *self {
+                Self::FireRate(rate) => weapon.period /= rate.max(0.01),
+                Self::Pierce(pierces) => {
+                    weapon.hit_budget = weapon.hit_budget.with_extra_pierces(pierces)
+                }
+                Self::Split(projectiles) => {
+                    weapon.projectiles += projectiles;
+                    weapon.spread += SPLIT_SPREAD * projectiles as f32;
+                }
+                Self::Orbiters(_) => {}
+            }
+        }
+
+        if let Self::Orbiters(orbiters) = *self {
+            upgrades.orbiters += orbiters;
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct UpgradeNode {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    /// Ids of the upgrades that must be taken before this one is offered.
+    #[serde(default)]
+    pub requires: Vec<String>,
+    pub modifier: Modifier,
+}
+
+#[derive(Debug, Deserialize, Resource)]
+#[serde(deny_unknown_fields)]
+pub struct UpgradeTree {
+    #[serde(rename = "upgrade")]
+    nodes: Vec<UpgradeNode>,
+}
+
+impl UpgradeTree {
+    pub fn parse(source: &str) -> Result<Self, toml::de::Error> {
+        let tree: Self = toml::from_str(source)?;
+
+        for node in tree.nodes.iter() {
+            if let Some(missing) = node
+                .requires
+                .iter()
+                .find(|id| !tree.nodes.iter().any(|n| n.id == **id))
+            {
+                return Err(toml::de::Error::custom(format!(
+                    "upgrade [{}] requires unknown upgrade [{missing}]",
+                    node.id
+                )));
+            }
+        }
+
+        Ok(tree)
+    }
+
+    pub fn node(&self, index: usize) -> &UpgradeNode {
+        &self.nodes[index]
+    }
+
+    /// Indices of the upgrades that can be taken after `taken`.
+    fn available(&self, taken: &[usize]) -> Vec<usize> {
+        let is_taken = |id: &String| taken.iter().any(|i| self.nodes[*i].id == *id);
+
+        (0..self.nodes.len())
+            .filter(|i| !taken.contains(i))
+            .filter(|i| self.nodes[*i].requires.iter().all(is_taken))
+            .collect()
+    }
+}
+
+/// The upgrades the player has taken and is being offered.
+#[derive(Debug, Default, Clone, Component)]
+pub struct Upgrades {
+    taken: Vec<usize>,
+    offer: Vec<usize>,
+    /// Level-ups that haven't been spent on an upgrade yet.
+    pending: u32,
+    /// Fissions not yet turned into experience.
+    fissions: u32,
+    orbiters: u32,
+}
+
+impl Upgrades {
+    /// Indices into the [UpgradeTree] of the upgrades on offer, in [CHOICE_KEYS] order.
+    pub fn offer(&self) -> &[usize] {
+        &self.offer
+    }
+
+    pub fn pending(&self) -> u32 {
+        self.pending
+    }
+
+    pub fn orbiters(&self) -> u32 {
+        self.orbiters
+    }
+
+    /// Offers new upgrades if the last offer was taken and a level-up is waiting.
+    fn refresh_offer(&mut self, tree: &UpgradeTree) {
+        if !self.offer.is_empty() || self.pending == 0 {
+            return;
+        }
+
+        let available = tree.available(&self.taken);
+        if available.is_empty() {
+            // the whole tree has been taken
+            self.pending = 0;
+            return;
+        }
+
+        self.offer = available
+            .choose_multiple(&mut rand::thread_rng(), CHOICE_KEYS.len())
+            .copied()
+            .collect();
+    }
+}
+
+pub(crate) fn gain_exp(
+    mut player: Query<(Mut<PlayerExp>, Mut<PlayerLevel>, Mut<Upgrades>), With<Player>>,
+    fissions: EventReader<FissionEvent>,
+    kills: EventReader<EnemyKilled>,
+    tree: Res<UpgradeTree>,
+) {
+    let Some((exp, level, upgrades)) = player.iter_mut().next() else {
+        return;
+    };
+
+    upgrades.fissions += fissions.peak_read().count() as u32;
+    exp.0 += upgrades.fissions / FISSIONS_PER_EXP;
+    upgrades.fissions %= FISSIONS_PER_EXP;
+    exp.0 += kills.peak_read().count() as u32 * KILL_EXP;
+
+    while exp.0 >= level.level_up_exp().max(1) {
+        exp.0 -= level.level_up_exp().max(1);
+        level.0 += 1;
+        upgrades.pending += 1;
+        info!("player reached level {}", level.0);
+    }
+
+    upgrades.refresh_offer(&tree);
+}
+
+fn choose_upgrade(
+    mut player: Query<(Mut<Inventory>, Mut<Upgrades>), With<Player>>,
+    keys: EventReader<KeyInput>,
+    tree: Res<UpgradeTree>,
+) {
+    let Some((inventory, upgrades)) = player.iter_mut().next() else {
+        return;
+    };
+
+    for event in keys.peak_read().filter(|k| k.state == KeyState::Pressed) {
+        let Some(choice) = CHOICE_KEYS.iter().position(|k| *k == event.code) else {
+            continue;
+        };
+        let Some(&index) = upgrades.offer.get(choice) else {
+            continue;
+        };
+
+        let node = tree.node(index);
+        node.modifier.apply(inventory, upgrades);
+        upgrades.taken.push(index);
+        upgrades.offer.clear();
+        upgrades.pending -= 1;
+        info!("took upgrade [{}]", node.id);
+
+        upgrades.refresh_offer(&tree);
+    }
+}
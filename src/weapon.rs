@@ -0,0 +1,523 @@
+use crate::{
+    bullet::{
+        pattern::{Aim, BulletPattern},
+        pierce::HitBudget,
+    },
+    player::Player,
+    should_run_game,
+};
+use std::f32::consts::PI;
+use winny::{asset::server::AssetServer, ecs::sets::IntoSystemStorage, prelude::*};
+
+/// Reloads the held weapon.
+const RELOAD_KEY: KeyCode = KeyCode::E;
+
+#[derive(Debug)]
+pub struct InventoryPlugin;
+
+impl Plugin for InventoryPlugin {
+    fn build(&mut self, app: &mut App) {
+        app.add_systems(
+            Schedule::Update,
+            (switch_weapons, update_ammo).run_if(should_run_game),
+        );
+    }
+}
+
+/// Something the player can shoot with.
+#[derive(Debug, Clone)]
+pub struct Weapon {
+    pub name: &'static str,
+    /// Seconds between shots.
+    pub period: f32,
+    /// Angle the projectiles of a shot are spread across, in radians.
+    pub spread: f32,
+    pub projectiles: u32,
+    pub speed: f32,
+    pub sound: &'static str,
+    pub volume: f32,
+    /// Whether shots home in on the nearest atom.
+    pub homing: bool,
+    pub hit_budget: HitBudget,
+    pub ammo: AmmoConfig,
+    /// Shots are charged by holding the trigger and fired on release.
+    pub charge: Option<ChargeConfig>,
+}
+
+impl Weapon {
+    pub fn pistol() -> Self {
+        Self {
+            name: "pistol",
+            period: 0.25,
+            spread: 0.,
+            projectiles: 1,
+            speed: 8.,
+            sound: "res/RPG_Essentials_Free/10_Battle_SFX/51_Flee_02.wav",
+            volume: 10.,
+            homing: false,
+            hit_budget: HitBudget::NONE,
+            // the pistol slowly refills so the player is never left unarmed
+            ammo: AmmoConfig {
+                magazine: 12,
+                reload: 1.,
+                reserve: 60,
+                regeneration: 1.,
+            },
+            charge: None,
+        }
+    }
+
+    pub fn scatter() -> Self {
+        Self {
+            name: "scatter",
+            period: 0.6,
+            spread: PI * 0.3,
+            projectiles: 5,
+            speed: 6.,
+            sound: "res/RPG_Essentials_Free/10_Battle_SFX/22_Slash_04.wav",
+            volume: 10.,
+            ammo: AmmoConfig {
+                magazine: 6,
+                reload: 1.4,
+                reserve: 24,
+                regeneration: 0.,
+            },
+            ..Self::pistol()
+        }
+    }
+
+    pub fn repeater() -> Self {
+        Self {
+            name: "repeater",
+            period: 0.08,
+            speed: 10.,
+            sound: "res/RPG_Essentials_Free/10_Battle_SFX/35_Miss_Evade_02.wav",
+            volume: 6.,
+            ammo: AmmoConfig {
+                magazine: 40,
+                reload: 1.8,
+                reserve: 160,
+                regeneration: 0.,
+            },
+            ..Self::pistol()
+        }
+    }
+
+    pub fn seeker() -> Self {
+        Self {
+            name: "seeker",
+            period: 0.45,
+            speed: 5.,
+            sound: "res/RPG_Essentials_Free/10_Battle_SFX/03_Claw_03.wav",
+            homing: true,
+            ammo: AmmoConfig {
+                magazine: 8,
+                reload: 1.2,
+                reserve: 32,
+                regeneration: 0.,
+            },
+            ..Self::pistol()
+        }
+    }
+
+    pub fn lance() -> Self {
+        Self {
+            name: "lance",
+            period: 0.7,
+            speed: 12.,
+            sound: "res/RPG_Essentials_Free/10_Battle_SFX/39_Block_03.wav",
+            hit_budget: HitBudget::new(3, 0.8),
+            ammo: AmmoConfig {
+                magazine: 4,
+                reload: 1.5,
+                reserve: 12,
+                regeneration: 0.,
+            },
+            ..Self::pistol()
+        }
+    }
+
+    pub fn cannon() -> Self {
+        Self {
+            name: "cannon",
+            period: 0.5,
+            speed: 4.,
+            sound: "res/RPG_Essentials_Free/10_Battle_SFX/15_Impact_flesh_02.wav",
+            volume: 12.,
+            ammo: AmmoConfig {
+                magazine: 3,
+                reload: 2.,
+                reserve: 9,
+                regeneration: 0.,
+            },
+            charge: Some(ChargeConfig {
+                time: 1.5,
+                size: 4.,
+                speed: 2.5,
+                damage: 5.,
+                neutrons: 6,
+            }),
+            ..Self::pistol()
+        }
+    }
+
+    /// The shape of a shot, pointing along the direction it is fired in.
+    pub fn pattern(&self) -> BulletPattern {
+        BulletPattern::Fan {
+            count: self.projectiles,
+            spread: self.spread,
+            speed: self.speed,
+            aim: Aim::Angle(0.),
+        }
+    }
+
+    pub fn audio(&self, server: &AssetServer) -> AudioBundle {
+        AudioBundle {
+            handle: server.load(self.sound),
+            playback_settings: PlaybackSettings::default().with_volume(self.volume),
+        }
+    }
+
+    pub fn empty_audio(server: &AssetServer) -> AudioBundle {
+        AudioBundle {
+            handle: server.load("res/RPG_Essentials_Free/10_UI_Menu_SFX/033_Denied_03.wav"),
+            playback_settings: PlaybackSettings::default().with_volume(8.0),
+        }
+    }
+
+    fn reload_audio(server: &AssetServer) -> AudioBundle {
+        AudioBundle {
+            handle: server.load("res/RPG_Essentials_Free/10_UI_Menu_SFX/070_Equip_10.wav"),
+            playback_settings: PlaybackSettings::default().with_volume(8.0),
+        }
+    }
+}
+
+/// How a charged shot grows, the multipliers are reached at full charge.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ChargeConfig {
+    /// Seconds to fully charge.
+    pub time: f32,
+    pub size: f32,
+    pub speed: f32,
+    pub damage: f32,
+    /// Extra neutrons released by the fission the shot causes.
+    pub neutrons: usize,
+}
+
+impl ChargeConfig {
+    /// Scales a full charge `multiplier` down to a charge `level` in the range [0, 1].
+    pub fn scale(multiplier: f32, level: f32) -> f32 {
+        1. + (multiplier - 1.) * level
+    }
+
+    pub fn extra_neutrons(&self, level: f32) -> usize {
+        (self.neutrons as f32 * level).round() as usize
+    }
+}
+
+/// How a weapon is loaded.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AmmoConfig {
+    /// Shots before reloading, 0 never runs out.
+    pub magazine: u32,
+    /// Seconds a reload takes.
+    pub reload: f32,
+    /// The most rounds carried outside the magazine.
+    pub reserve: u32,
+    /// Rounds added to the reserve every second, even while the weapon isn't held.
+    pub regeneration: f32,
+}
+
+impl AmmoConfig {
+    pub const INFINITE: Self = Self {
+        magazine: 0,
+        reload: 0.,
+        reserve: 0,
+        regeneration: 0.,
+    };
+
+    pub fn is_infinite(&self) -> bool {
+        self.magazine == 0
+    }
+}
+
+/// Seconds the ammo readout flashes after the trigger is pulled on an empty weapon.
+const EMPTY_FLASH: f32 = 0.3;
+
+/// The rounds a weapon has left.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Magazine {
+    loaded: u32,
+    reserve: u32,
+    /// Seconds until the reload finishes.
+    reloading: Option<f32>,
+    /// Partial rounds regenerated so far.
+    regenerated: f32,
+    empty: f32,
+}
+
+impl Magazine {
+    pub fn full(config: &AmmoConfig) -> Self {
+        Self {
+            loaded: config.magazine,
+            reserve: config.reserve,
+            reloading: None,
+            regenerated: 0.,
+            empty: 0.,
+        }
+    }
+
+    pub fn loaded(&self) -> u32 {
+        self.loaded
+    }
+
+    pub fn reserve(&self) -> u32 {
+        self.reserve
+    }
+
+    /// How far along the current reload is, in the range [0, 1].
+    pub fn reload_progress(&self, config: &AmmoConfig) -> Option<f32> {
+        self.reloading
+            .map(|remaining| 1. - remaining / config.reload.max(f32::EPSILON))
+    }
+
+    /// Whether the trigger was recently pulled with nothing to fire.
+    pub fn is_flashing_empty(&self) -> bool {
+        self.empty > 0.
+    }
+
+    fn start_reload(&mut self, config: &AmmoConfig) {
+        if self.reloading.is_none() && self.loaded < config.magazine && self.reserve > 0 {
+            self.reloading = Some(config.reload);
+        }
+    }
+}
+
+/// The result of pulling the trigger.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Trigger {
+    Fired,
+    Reloading,
+    /// Nothing loaded and nothing to reload with.
+    Empty,
+}
+
+/// The weapons the player holds, switched with the number keys or the scroll wheel.
+///
+/// Every weapon keeps its own [Magazine], only the held weapon reloads.
+#[derive(Debug, Clone, Component)]
+pub struct Inventory {
+    weapons: Vec<Weapon>,
+    magazines: Vec<Magazine>,
+    current: usize,
+}
+
+impl Default for Inventory {
+    fn default() -> Self {
+        Self::new(vec![
+            Weapon::pistol(),
+            Weapon::scatter(),
+            Weapon::repeater(),
+            Weapon::seeker(),
+            Weapon::lance(),
+            Weapon::cannon(),
+        ])
+    }
+}
+
+impl Inventory {
+    pub fn new(weapons: Vec<Weapon>) -> Self {
+        assert!(
+            !weapons.is_empty(),
+            "an inventory needs at least one weapon"
+        );
+
+        Self {
+            magazines: weapons.iter().map(|w| Magazine::full(&w.ammo)).collect(),
+            weapons,
+            current: 0,
+        }
+    }
+
+    pub fn current(&self) -> &Weapon {
+        &self.weapons[self.current]
+    }
+
+    pub fn magazine(&self) -> &Magazine {
+        &self.magazines[self.current]
+    }
+
+    pub fn current_index(&self) -> usize {
+        self.current
+    }
+
+    pub fn weapons(&self) -> &[Weapon] {
+        &self.weapons
+    }
+
+    pub fn weapons_mut(&mut self) -> &mut [Weapon] {
+        &mut self.weapons
+    }
+
+    /// Uses a round of the held weapon, reloading when the magazine runs dry.
+    pub fn pull_trigger(&mut self) -> Trigger {
+        let config = self.weapons[self.current].ammo;
+        let magazine = &mut self.magazines[self.current];
+        if config.is_infinite() {
+            return Trigger::Fired;
+        }
+        if magazine.reloading.is_some() {
+            return Trigger::Reloading;
+        }
+
+        if magazine.loaded == 0 {
+            magazine.start_reload(&config);
+            if magazine.reloading.is_some() {
+                return Trigger::Reloading;
+            }
+
+            magazine.empty = EMPTY_FLASH;
+            return Trigger::Empty;
+        }
+
+        magazine.loaded -= 1;
+        if magazine.loaded == 0 {
+            magazine.start_reload(&config);
+        }
+        Trigger::Fired
+    }
+
+    /// Starts reloading the held weapon, if it isn't full.
+    pub fn reload(&mut self) {
+        let config = self.weapons[self.current].ammo;
+        self.magazines[self.current].start_reload(&config);
+    }
+
+    /// Refills every weapon's reserve by a `fraction` of its maximum.
+    pub fn restock(&mut self, fraction: f32) {
+        for (weapon, magazine) in self.weapons.iter().zip(self.magazines.iter_mut()) {
+            let rounds = (weapon.ammo.reserve as f32 * fraction).ceil() as u32;
+            magazine.reserve = (magazine.reserve + rounds).min(weapon.ammo.reserve);
+        }
+    }
+
+    /// Advances reloads and regeneration, returns whether a reload finished.
+    fn tick(&mut self, delta: f32) -> bool {
+        let mut reloaded = false;
+
+        for (i, (weapon, magazine)) in self
+            .weapons
+            .iter()
+            .zip(self.magazines.iter_mut())
+            .enumerate()
+        {
+            let config = &weapon.ammo;
+            magazine.empty = (magazine.empty - delta).max(0.);
+
+            if magazine.reserve < config.reserve {
+                magazine.regenerated += config.regeneration * delta;
+                let rounds = magazine.regenerated.floor();
+                magazine.regenerated -= rounds;
+                magazine.reserve = (magazine.reserve + rounds as u32).min(config.reserve);
+            } else {
+                magazine.regenerated = 0.;
+            }
+
+            if i != self.current {
+                continue;
+            }
+
+            let Some(remaining) = magazine.reloading.as_mut() else {
+                continue;
+            };
+            *remaining -= delta;
+            if *remaining <= 0. {
+                let rounds = (config.magazine - magazine.loaded).min(magazine.reserve);
+                magazine.loaded += rounds;
+                magazine.reserve -= rounds;
+                magazine.reloading = None;
+                reloaded = true;
+            }
+        }
+
+        reloaded
+    }
+
+    /// Selects the weapon in `slot`, empty slots are ignored.
+    pub fn select(&mut self, slot: usize) {
+        if slot < self.weapons.len() {
+            self.current = slot;
+        }
+    }
+
+    /// Moves the selection by `step`, wrapping around.
+    pub fn cycle(&mut self, step: i32) {
+        let len = self.weapons.len() as i32;
+        self.current = (self.current as i32 + step).rem_euclid(len) as usize;
+    }
+}
+
+const SLOT_KEYS: [KeyCode; 9] = [
+    KeyCode::Key1,
+    KeyCode::Key2,
+    KeyCode::Key3,
+    KeyCode::Key4,
+    KeyCode::Key5,
+    KeyCode::Key6,
+    KeyCode::Key7,
+    KeyCode::Key8,
+    KeyCode::Key9,
+];
+
+fn switch_weapons(
+    mut player: Query<Mut<Inventory>, With<Player>>,
+    keys: EventReader<KeyInput>,
+    wheel: EventReader<MouseWheel>,
+) {
+    let Ok(inventory) = player.get_single_mut() else {
+        return;
+    };
+
+    for event in keys.peak_read() {
+        if event.state != KeyState::Pressed {
+            continue;
+        }
+
+        if let Some(slot) = SLOT_KEYS.iter().position(|k| *k == event.code) {
+            inventory.select(slot);
+        }
+    }
+
+    for event in wheel.peak_read() {
+        // scrolling down moves to the next weapon
+        if event.1 < 0. {
+            inventory.cycle(1);
+        } else if event.1 > 0. {
+            inventory.cycle(-1);
+        }
+    }
+}
+
+fn update_ammo(
+    mut player: Query<Mut<Inventory>, With<Player>>,
+    keys: EventReader<KeyInput>,
+    mut commands: Commands,
+    server: Res<AssetServer>,
+    delta: Res<DeltaTime>,
+) {
+    let Ok(inventory) = player.get_single_mut() else {
+        return;
+    };
+
+    if keys
+        .peak_read()
+        .any(|e| e.code == RELOAD_KEY && e.state == KeyState::Pressed)
+    {
+        inventory.reload();
+    }
+
+    if inventory.tick(delta.delta) {
+        commands.spawn(Weapon::reload_audio(&server));
+    }
+}